@@ -25,15 +25,27 @@ fn bench(c: &mut Criterion) {
                 black_box(BufReader::new(file)),
                 Settings {
                     R003: Some(Default::default()),
+                    R006: Some(Default::default()),
+                    R011: Some(Default::default()),
+                    R020: Some(Default::default()),
                     R024: Some(Default::default()),
                     R025: Some(Default::default()),
                     R028: Some(Default::default()),
+                    R029: Some(Default::default()),
                     R030: Some(Default::default()),
+                    R031: Some(Default::default()),
                     R035: Some(Default::default()),
                     R036: Some(Default::default()),
                     R038: Some(Default::default()),
+                    R043: Some(Default::default()),
+                    R044: Some(Default::default()),
                     R048: Some(Default::default()),
+                    R049: Some(Default::default()),
+                    R050: Some(Default::default()),
+                    R055: Some(Default::default()),
                     R058: Some(Default::default()),
+                    R064: Some(Default::default()),
+                    R071: Some(Default::default()),
                     ..Default::default()
                 },
                 &false,
@@ -49,6 +49,22 @@ fn prepare_{name}() {{
         .unwrap();
     }
 
+    for entry in glob("tests/fixtures/compile/*.jsonl").expect("Failed to read glob pattern") {
+        let path = entry.unwrap();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+
+        write!(
+            file,
+            r#"
+#[test]
+fn compile_{name}() {{
+    check_compile("compile/{name}")
+}}
+"#
+        )
+        .unwrap();
+    }
+
     for entry in glob("tests/fixtures/indicators/*.jsonl").expect("Failed to read glob pattern") {
         let path = entry.unwrap();
         let name = path.file_stem().unwrap().to_str().unwrap();
@@ -83,6 +99,7 @@ fn prepare_{name}() {{
             file,
             r#"
 #[test]
+#[allow(clippy::needless_update)] // if the indicator has only one setting
 fn {function}() {{
     check_indicators("indicators/{name}", Settings {{
         {ident}: Some({setting}),
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::indicators::Indicators;
+
+/// A row of the CSV file of exchange rates.
+#[derive(Deserialize)]
+struct Row {
+    currency: String,
+    /// A date like "2024-01-31", a year like "2024", or blank.
+    date: Option<String>,
+    /// The value of one unit of the currency, in the main currency.
+    rate: f64,
+}
+
+/// Exchange rates, to convert monetary values to the main currency.
+#[derive(Debug, Default)]
+pub struct ExchangeRates {
+    /// The main currency.
+    currency: String,
+    /// The rates for each currency, by date.
+    dates: HashMap<String, BTreeMap<NaiveDate, f64>>,
+    /// The rates for each currency, by year.
+    years: HashMap<String, HashMap<i32, f64>>,
+    /// The rate for each currency, regardless of date.
+    rates: HashMap<String, f64>,
+}
+
+impl ExchangeRates {
+    /// Read exchange rates from a CSV file with the columns: currency, date, rate.
+    ///
+    /// # Errors
+    ///
+    pub fn from_path(path: &str, currency: String) -> Result<Self> {
+        let mut item = Self {
+            currency,
+            ..Default::default()
+        };
+
+        let mut reader = csv::Reader::from_path(path).with_context(|| format!("exchange_rates: {path}"))?;
+        for (i, result) in reader.deserialize().enumerate() {
+            let row: Row = result.with_context(|| format!("exchange_rates: {path}"))?;
+            match row.date.as_deref().map(str::trim) {
+                None | Some("") => {
+                    item.rates.insert(row.currency, row.rate);
+                }
+                Some(date) if date.len() == 4 => match date.parse() {
+                    Ok(year) => {
+                        item.years.entry(row.currency).or_default().insert(year, row.rate);
+                    }
+                    Err(e) => bail!("exchange_rates: {path}: row {} has an invalid year [{e}]", i + 2),
+                },
+                Some(date) => match date.parse() {
+                    Ok(date) => {
+                        item.dates.entry(row.currency).or_default().insert(date, row.rate);
+                    }
+                    Err(e) => bail!("exchange_rates: {path}: row {} has an invalid date [{e}]", i + 2),
+                },
+            }
+        }
+
+        Ok(item)
+    }
+
+    // Use the rate on the closest earlier date in the same year, then the rate for the year, then the rate on the
+    // closest earlier date, then the rate regardless of date.
+    fn rate(&self, currency: &str, date: Option<NaiveDate>) -> Option<f64> {
+        date.and_then(|date| {
+            let earlier = self
+                .dates
+                .get(currency)
+                .and_then(|dates| dates.range(..=date).next_back());
+
+            earlier
+                .filter(|(other, _)| other.year() == date.year())
+                .map(|(_, rate)| *rate)
+                .or_else(|| {
+                    self.years
+                        .get(currency)
+                        .and_then(|years| years.get(&date.year()).copied())
+                })
+                .or_else(|| earlier.map(|(_, rate)| *rate))
+        })
+        .or_else(|| self.rates.get(currency).copied())
+    }
+
+    /// Convert the amounts of the tender, bids, awards and contracts in other currencies to the main currency, using
+    /// the bid's date, the award's date or the contract's signature date, or else the release's date. Count the values
+    /// that are converted, and that are skipped for lack of an exchange rate.
+    pub fn convert(&self, item: &mut Indicators, release: &mut Map<String, Value>) {
+        let release_date = release.get("date").and_then(parse_date);
+
+        if let Some(Value::Object(tender)) = release.get_mut("tender")
+            && let Some(value) = tender.get_mut("value")
+        {
+            self.convert_value(item, value, release_date);
+        }
+
+        if let Some(Value::Object(bids)) = release.get_mut("bids")
+            && let Some(Value::Array(details)) = bids.get_mut("details")
+        {
+            self.convert_array(item, details, "date", release_date);
+        }
+
+        if let Some(Value::Array(awards)) = release.get_mut("awards") {
+            self.convert_array(item, awards, "date", release_date);
+        }
+
+        if let Some(Value::Array(contracts)) = release.get_mut("contracts") {
+            self.convert_array(item, contracts, "dateSigned", release_date);
+        }
+    }
+
+    fn convert_array(&self, item: &mut Indicators, objects: &mut [Value], field: &str, default: Option<NaiveDate>) {
+        for object in objects {
+            let date = object.get(field).and_then(parse_date).or(default);
+
+            if let Some(value) = object.get_mut("value") {
+                self.convert_value(item, value, date);
+            }
+        }
+    }
+
+    fn convert_value(&self, item: &mut Indicators, value: &mut Value, date: Option<NaiveDate>) {
+        if let Value::Object(value) = value
+            && let Some(Value::String(currency)) = value.get("currency")
+            && let Some(Value::Number(amount)) = value.get("amount")
+            && let Some(amount) = amount.as_f64()
+            && *currency != self.currency
+        {
+            if let Some(rate) = self.rate(currency, date) {
+                value.insert("amount".into(), Value::from(amount * rate));
+                value.insert("currency".into(), Value::String(self.currency.clone()));
+                item.converted_values += 1;
+            } else {
+                item.skipped_values += 1;
+            }
+        }
+    }
+
+    /// Add the counts of converted and skipped values to the "Meta" key.
+    pub fn finalize(item: &mut Indicators) {
+        let meta = &mut item.meta.entry("exchange_rates".into()).or_default().0;
+        meta.insert("converted".into(), item.converted_values as f64);
+        meta.insert("skipped".into(), item.skipped_values as f64);
+    }
+}
+
+fn parse_date(value: &Value) -> Option<NaiveDate> {
+    value
+        .as_str()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.date_naive())
+}
@@ -1,14 +1,27 @@
+pub mod exchange_rates;
 pub mod r003;
+pub mod r006;
+pub mod r011;
 pub mod r018;
+pub mod r020;
 pub mod r024;
 pub mod r025;
 pub mod r028;
+pub mod r029;
 pub mod r030;
+pub mod r031;
 pub mod r035;
 pub mod r036;
 pub mod r038;
+pub mod r043;
+pub mod r044;
 pub mod r048;
+pub mod r049;
+pub mod r050;
+pub mod r055;
 pub mod r058;
+pub mod r064;
+pub mod r071;
 pub mod util;
 
 use std::collections::{HashMap, HashSet};
@@ -26,6 +39,10 @@ use serde_json::{Map, Value};
 pub enum Codelist {
     BidStatus,
     AwardStatus,
+    ProcurementMethod,
+    TenderStatus,
+    ContractStatus,
+    PartyRole,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -36,6 +53,7 @@ pub struct Defaults {
     pub bid_status: Option<String>,
     pub award_status: Option<String>,
     pub party_roles: Option<bool>,
+    pub timezone: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -58,6 +76,26 @@ pub struct Modifications {
     pub prefix_buyer_or_procuring_entity_id: Option<String>,
     pub prefix_tenderer_or_supplier_id: Option<String>,
     pub split_procurement_method_details: Option<String>,
+    pub date_formats: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all(deserialize = "lowercase"))]
+pub enum Cast {
+    Number,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub path: String,
+    pub delete: Option<bool>,
+    pub rename: Option<String>,
+    pub default: Option<String>,
+    pub map: Option<HashMap<String, String>>,
+    pub pattern: Option<String>,
+    pub replacement: Option<String>,
+    pub cast: Option<Cast>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -90,6 +128,24 @@ pub struct R003 {
     pub procurement_method_details: Option<HashMap<String, i64>>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R006 {
+    pub threshold: Option<u64>,
+    pub procurement_methods: Option<String>,
+    pub procurement_method_details: Option<HashMap<String, u64>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R011 {
+    pub procurement_threshold: Option<u64>,
+    pub margin: Option<f64>,
+    pub window: Option<u32>, // days
+    pub digits: Option<usize>,
+    pub threshold: Option<usize>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct R018 {
@@ -103,6 +159,20 @@ pub struct R025 {
     threshold: Option<f64>, // ratio
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R029 {
+    pub threshold: Option<usize>,
+    pub minimum_contracting_processes: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R031 {
+    pub procurement_methods: Option<String>,
+    pub procurement_method_details: Option<HashMap<String, String>>, // lower|upper days
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct R038 {
@@ -111,6 +181,13 @@ pub struct R038 {
     minimum_contracting_processes: Option<usize>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R043 {
+    pub threshold: Option<u32>, // days
+    pub registration_dates: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct R048 {
@@ -119,6 +196,29 @@ pub struct R048 {
     pub minimum_contracting_processes: Option<usize>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R049 {
+    pub threshold: Option<f64>, // ratio
+    pub minimum_contracting_processes: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R050 {
+    pub procurement_methods: Option<String>,
+    pub procurement_method_details: Option<String>,
+    pub window: Option<u32>, // days
+    pub threshold: Option<usize>,
+    pub minimum_contracting_processes: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct R064 {
+    pub threshold: Option<usize>, // days
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[allow(non_snake_case)]
@@ -129,22 +229,36 @@ pub struct Settings {
     pub redactions: Option<Redactions>,
     pub corrections: Option<Corrections>,
     pub modifications: Option<Modifications>,
+    pub rules: Option<HashMap<String, Rule>>,
     // indicators command.
     pub currency: Option<String>,
+    pub exchange_rates: Option<String>,
     pub no_price_comparison_procurement_methods: Option<String>,
     pub price_comparison_procurement_methods: Option<String>,
     pub exclusions: Option<Exclusions>,
     pub R003: Option<R003>,
+    pub R006: Option<R006>,
+    pub R011: Option<R011>,
     pub R018: Option<R018>,
+    pub R020: Option<Empty>,
     pub R024: Option<FloatThreshold>, // ratio
     pub R025: Option<R025>,
     pub R028: Option<Empty>,
+    pub R029: Option<R029>,
     pub R030: Option<Empty>,
+    pub R031: Option<R031>,
     pub R035: Option<IntegerThreshold>, // count
     pub R036: Option<Empty>,
     pub R038: Option<R038>,
+    pub R043: Option<R043>,
+    pub R044: Option<Empty>,
     pub R048: Option<R048>,
+    pub R049: Option<R049>,
+    pub R050: Option<R050>,
+    pub R055: Option<FloatThreshold>, // ratio
     pub R058: Option<FloatThreshold>, // ratio
+    pub R064: Option<R064>,
+    pub R071: Option<FloatThreshold>, // ratio
 }
 
 // Final results.
@@ -157,35 +271,140 @@ pub enum Group {
     Tenderer,
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub enum Indicator {
     R003,
+    R006,
+    R011,
     R018,
+    R020,
     R024,
     R025,
     R028,
+    R029,
     R030,
+    R031,
     R035,
     R036,
     R038,
+    R043,
+    R044,
     R048,
+    R049,
+    R050,
+    R055,
     R058,
+    R064,
+    R071,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Maps {
+    /// The buyer for each flagged `ocid`.
+    pub ocid_buyer_r006: HashMap<String, String>,
+    /// The buyer for each `ocid` in the flagged window of awards just below the procurement threshold.
+    pub ocid_buyer_r011: HashMap<String, String>,
     /// The buyer for each `ocid` in which at least one bid is disqualified.
     pub ocid_buyer_r038: HashMap<String, String>,
     /// The procuring entity for each `ocid` in which at least one bid is disqualified.
     pub ocid_procuringentity_r038: HashMap<String, String>,
+    /// The buyer for each `ocid` awarded to the buyer's dominant supplier.
+    pub ocid_buyer_r049: HashMap<String, String>,
+    /// The buyer for each non-competitive `ocid` awarded to a flagged supplier.
+    pub ocid_buyer_r050: HashMap<String, String>,
+    /// The buyer for each flagged `ocid`.
+    pub ocid_buyer_r071: HashMap<String, String>,
     /// The tenderers that submitted bids for each `ocid`.
     pub ocid_tenderer: HashMap<String, HashSet<String>>,
     /// The flagged tenderers for each flagged `ocid`.
+    pub ocid_tenderer_r006: HashMap<String, HashSet<String>>,
     pub ocid_tenderer_r024: HashMap<String, HashSet<String>>,
     pub ocid_tenderer_r028: HashMap<String, HashSet<String>>,
+    pub ocid_tenderer_r029: HashMap<String, HashSet<String>>,
     pub ocid_tenderer_r030: HashMap<String, HashSet<String>>,
     pub ocid_tenderer_r035: HashMap<String, HashSet<String>>,
+    pub ocid_tenderer_r043: HashMap<String, HashSet<String>>,
+    pub ocid_tenderer_r044: HashMap<String, HashSet<String>>,
+    /// The shared contact details (like "email") for each flagged `ocid`.
+    pub ocid_attribute_r044: HashMap<String, HashSet<String>>,
+    /// The dominant supplier for each `ocid` awarded to a flagged buyer's dominant supplier.
+    pub ocid_tenderer_r049: HashMap<String, HashSet<String>>,
+    /// The flagged suppliers for each non-competitive `ocid` awarded by a flagged buyer.
+    pub ocid_tenderer_r050: HashMap<String, HashSet<String>>,
+    /// The flagged tenderers for each flagged `ocid`.
     pub ocid_tenderer_r058: HashMap<String, HashSet<String>>,
+    pub ocid_tenderer_r071: HashMap<String, HashSet<String>>,
+}
+
+/// A result for a contracting process, as a row of the CSV output.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Row {
+    pub ocid: String,
+    pub subject: Group,
+    pub code: Indicator,
+    pub result: f64,
+    pub buyer_id: String,
+    pub procuring_entity_id: String,
+    pub tenderer_id: String,
+    pub created_at: String,
+}
+
+/// The awards and bids of a lot, or of a contracting process without lots.
+pub struct Competition<'a> {
+    /// The lot's ID, if the contracting process has lots.
+    pub lot_id: Option<String>,
+    pub awards: Vec<&'a Value>,
+    pub bids: Vec<&'a Value>,
+}
+
+/// The increase from the awarded value to the contract value of a contracting process.
+#[derive(Debug)]
+pub struct ValueIncrease {
+    /// The increase, as a ratio of the awarded value.
+    pub ratio: f64,
+    /// Whether the value of an amended contract is greater than its award's value.
+    pub amended: bool,
+    pub buyer_id: Option<String>,
+    pub supplier_ids: HashSet<String>,
+}
+
+/// The awards of a buyer.
+#[derive(Debug, Default)]
+pub struct BuyerAwards {
+    /// The number of active awards with a value.
+    pub count: usize,
+    /// The date, item classifications and `ocid` of each award just below the procurement threshold.
+    pub below: Vec<(i64, HashSet<String>, String)>,
+}
+
+/// The awards of a buyer, by supplier.
+#[derive(Debug, Default)]
+pub struct BuyerSuppliers {
+    /// The number of contracting processes with awards.
+    pub count: usize,
+    /// The awarded value and the contracting processes for each `awards/suppliers/id`.
+    pub suppliers: HashMap<String, (f64, Vec<String>)>,
+}
+
+/// The non-competitive contracting processes in which a buyer awarded a supplier.
+#[derive(Debug, Default)]
+pub struct NonCompetitiveAwards {
+    /// The date of the earliest award to the supplier in each contracting process.
+    pub timestamps: Vec<i64>,
+    /// The contracting processes (if the `--map` flag is set).
+    pub ocids: HashSet<String>,
+}
+
+/// The competitions in which two tenderers submitted bids.
+#[derive(Debug, Default)]
+pub struct CoBids {
+    /// The number of competitions.
+    pub count: usize,
+    /// The number of competitions won by each tenderer.
+    pub wins: [usize; 2],
+    /// The contracting processes (if the `--map` flag is set).
+    pub ocids: HashSet<String>,
 }
 
 #[derive(Debug, Default)]
@@ -200,22 +419,48 @@ pub struct RoundMap(#[serde(serialize_with = "round")] IndexMap<String, f64>);
 #[derive(Debug, Default)]
 pub struct Indicators {
     pub results: IndexMap<Group, IndexMap<String, HashMap<Indicator, f64>>>,
-    pub meta: HashMap<Indicator, RoundMap>,
+    pub meta: HashMap<String, RoundMap>,
     pub maps: Maps,
     pub currency: Option<String>,
-    /// The percentage difference between the winning bid and the second-lowest valid bid for each `ocid`.
-    pub second_lowest_bid_ratios: HashMap<String, f64>,
-    pub winner_and_lowest_non_winner: HashMap<String, [String; 2]>,
+    /// The number of monetary values whose amounts are converted to the main currency.
+    pub converted_values: usize,
+    /// The number of monetary values whose amounts aren't converted, for lack of an exchange rate.
+    pub skipped_values: usize,
+    /// The number of flagged contracting processes for each `buyer/id`.
+    pub r006_buyer: HashMap<String, usize>,
+    /// The number of flagged contracting processes for each `awards/suppliers/id`.
+    pub r006_tenderer: HashMap<String, usize>,
+    /// The awards for each `buyer/id`.
+    pub r011_awards: HashMap<String, BuyerAwards>,
+    /// The percentage difference between the winning bid and the second-lowest valid bid for each `ocid` and
+    /// lot ID (if the contracting process has lots).
+    pub second_lowest_bid_ratios: HashMap<(String, Option<String>), f64>,
+    /// The winner's and lowest non-winner's tenderer IDs for each `ocid` and lot ID. A consortium has multiple IDs.
+    pub winner_and_lowest_non_winner: HashMap<(String, Option<String>), [Vec<String>; 2]>,
     /// The ratio of winning bids to submitted bids for each `bids/details/tenderers/id`.
     pub r025_tenderer: HashMap<String, Fraction>,
+    /// The competitions in which both tenderers submitted bids, for each pair of `bids/details/tenderers/id`.
+    pub r029_pairs: HashMap<(String, String), CoBids>,
+    /// The evaluation period in days for each `ocid`, by `tender/procurementMethodDetails`.
+    pub r031_durations: HashMap<String, HashMap<String, f64>>,
     /// The ratio of disqualified bids to submitted bids for each `buyer/id`.
     pub r038_buyer: HashMap<String, Fraction>,
     /// The ratio of disqualified bids to submitted bids for each `tender/procuringEntity/id`.
     pub r038_procuring_entity: HashMap<String, Fraction>,
     /// The ratio of disqualified bids to submitted bids for each `bids/details/tenderers/id`.
     pub r038_tenderer: HashMap<String, Fraction>,
+    /// The youngest age in days of each flagged `awards/suppliers/id`.
+    pub r043_ages: HashMap<String, f64>,
     /// The item classifications for each `bids/details/tenderers/id`.
     pub r048_classifications: HashMap<String, (usize, HashSet<String>)>,
+    /// The awards for each `buyer/id`, by supplier.
+    pub r049_buyers: HashMap<String, BuyerSuppliers>,
+    /// The non-competitive awards for each pair of `buyer/id` and `awards/suppliers/id`.
+    pub r050_pairs: HashMap<(String, String), NonCompetitiveAwards>,
+    /// The longest delay in days between an award's date and its contract's signature for each `ocid`.
+    pub r064_delays: HashMap<String, f64>,
+    /// The increase from the awarded value to the contract value for each `ocid`.
+    pub r071_increases: HashMap<String, ValueIncrease>,
     /// Whether to map contracting processes to organizations.
     pub map: bool,
 }
@@ -250,6 +495,22 @@ pub trait Calculate {
 
 // Trait implementations.
 
+impl AddAssign for CoBids {
+    fn add_assign(&mut self, other: Self) {
+        self.count += other.count;
+        self.wins[0] += other.wins[0];
+        self.wins[1] += other.wins[1];
+        self.ocids.extend(other.ocids);
+    }
+}
+
+impl AddAssign for NonCompetitiveAwards {
+    fn add_assign(&mut self, other: Self) {
+        self.timestamps.extend(other.timestamps);
+        self.ocids.extend(other.ocids);
+    }
+}
+
 // https://en.wikipedia.org/wiki/Mediant_(mathematics)
 impl AddAssign for Fraction {
     fn add_assign(&mut self, other: Self) {
@@ -329,11 +590,27 @@ macro_rules! set_result {
 }
 pub(crate) use set_result;
 
+// A contracting process' result is the worst result of its lots, like the lowest (min) or highest (max) value.
+macro_rules! set_lot_result {
+    ( $item:ident , $ocid:expr , $indicator:ident , $value:expr , $worst:ident ) => {
+        $item
+            .results
+            .entry(crate::indicators::Group::OCID)
+            .or_default()
+            .entry($ocid.to_owned())
+            .or_default()
+            .entry(crate::indicators::Indicator::$indicator)
+            .and_modify(|value| *value = value.$worst($value))
+            .or_insert($value)
+    };
+}
+pub(crate) use set_lot_result;
+
 macro_rules! set_meta {
     ( $item:ident , $indicator:ident , $key:expr , $value:expr ) => {
         $item
             .meta
-            .entry(crate::indicators::Indicator::$indicator)
+            .entry(stringify!($indicator).to_owned())
             .or_default()
             .0
             .insert($key.to_owned(), $value)
@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+
+use log::warn;
+use serde_json::{Map, Value};
+
+use crate::indicators::{is_status, reduce_map, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+use crate::parse_pipe_separated_value_with_default;
+
+#[derive(Default)]
+pub struct R006 {
+    threshold: Option<u64>,
+    procurement_methods: HashSet<String>,
+    procurement_method_details: HashMap<String, u64>,
+    currency: Option<String>,
+}
+
+impl Calculate for R006 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R006).unwrap_or_default();
+
+        if setting.threshold.is_none() && setting.procurement_method_details.is_none() {
+            warn!("R006 has no effect if neither threshold nor procurement_method_details is set.");
+        }
+
+        Self {
+            threshold: setting.threshold,
+            procurement_methods: parse_pipe_separated_value_with_default(
+                setting.procurement_methods,
+                String::from("limited"),
+            ),
+            procurement_method_details: setting.procurement_method_details.unwrap_or_default(),
+            currency: settings.currency.clone(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(Value::Object(tender)) = release.get("tender") else {
+            return;
+        };
+
+        // A direct award has a matching procurement method or procurement method details.
+        let threshold = if let Some(Value::String(details)) = tender.get("procurementMethodDetails")
+            && let Some(threshold) = self.procurement_method_details.get(details)
+        {
+            *threshold
+        } else if let Some(threshold) = self.threshold
+            && let Some(Value::String(procurement_method)) = tender.get("procurementMethod")
+            && self.procurement_methods.contains(procurement_method)
+        {
+            threshold
+        } else {
+            return;
+        };
+
+        let Some(Value::Array(awards)) = release.get("awards") else {
+            return;
+        };
+
+        let mut amount = None;
+        let mut supplier_ids = HashSet::new();
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(value) = award.get("value")
+                && let Some(award_amount) = item.get_amount(self.currency.as_ref(), value)
+            {
+                *amount.get_or_insert(0.0) += award_amount;
+
+                if let Some(Value::Array(suppliers)) = award.get("suppliers") {
+                    for supplier in suppliers {
+                        if let Some(Value::String(id)) = supplier.get("id") {
+                            supplier_ids.insert(id);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(amount) = amount
+            && amount > threshold as f64
+        {
+            set_result!(item, OCID, ocid, R006, amount);
+
+            if let Some(Value::Object(buyer)) = release.get("buyer")
+                && let Some(Value::String(id)) = buyer.get("id")
+            {
+                *item.r006_buyer.entry(id.clone()).or_default() += 1;
+                if item.map {
+                    item.maps.ocid_buyer_r006.insert(ocid.to_owned(), id.clone());
+                }
+            }
+            for id in supplier_ids {
+                *item.r006_tenderer.entry(id.clone()).or_default() += 1;
+                set_tenderer_map!(item, ocid_tenderer_r006, ocid.to_owned(), id.clone());
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        for (id, count) in std::mem::take(&mut other.r006_buyer) {
+            *item.r006_buyer.entry(id).or_default() += count;
+        }
+        for (id, count) in std::mem::take(&mut other.r006_tenderer) {
+            *item.r006_tenderer.entry(id).or_default() += count;
+        }
+
+        reduce_map!(item, other, ocid_buyer_r006);
+        reduce_map!(item, other, ocid_tenderer_r006);
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        for (id, count) in std::mem::take(&mut item.r006_buyer) {
+            set_result!(item, Buyer, id, R006, count as f64);
+        }
+        for (id, count) in std::mem::take(&mut item.r006_tenderer) {
+            set_result!(item, Tenderer, id, R006, count as f64);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::DateTime;
+use log::warn;
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{is_status, set_meta, set_result, BuyerAwards, Calculate, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R011 {
+    procurement_threshold: Option<u64>,
+    margin: f64,
+    window: i64,
+    digits: usize,
+    threshold: Option<usize>, // resolved in finalize()
+    currency: Option<String>,
+}
+
+// Returns the upper fence of Q3 + 1.5(IQR), unless the IQR is 0.
+fn upper_fence(item: &mut Indicators, values: Vec<f64>, prefix: &str) -> Option<f64> {
+    let mut data = Data::new(values);
+    let q1 = data.lower_quartile();
+    let q3 = data.upper_quartile();
+    set_meta!(item, R011, format!("{prefix}_q1"), q1);
+    set_meta!(item, R011, format!("{prefix}_q3"), q3);
+    // Skip if the IQR is 0; otherwise, at least 25% are flagged.
+    if q3 > q1 {
+        let upper_fence = (q3 - q1).mul_add(1.5, q3); // q3 + IQR * 1.5
+        set_meta!(item, R011, format!("{prefix}_upper_fence"), upper_fence);
+        Some(upper_fence)
+    } else {
+        None
+    }
+}
+
+impl Calculate for R011 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R011).unwrap_or_default();
+
+        if setting.procurement_threshold.is_none() {
+            warn!("R011 has no effect if procurement_threshold isn't set.");
+        }
+
+        Self {
+            procurement_threshold: setting.procurement_threshold,
+            margin: setting.margin.unwrap_or(0.1),
+            window: i64::from(setting.window.unwrap_or(30)),
+            digits: setting.digits.unwrap_or(2),
+            threshold: setting.threshold,
+            currency: settings.currency.clone(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(procurement_threshold) = self.procurement_threshold else {
+            return;
+        };
+        let upper = procurement_threshold as f64;
+        let lower = upper * (1.0 - self.margin);
+
+        if let Some(Value::Object(buyer)) = release.get("buyer")
+            && let Some(Value::String(buyer_id)) = buyer.get("id")
+            && let Some(Value::Array(awards)) = release.get("awards")
+        {
+            for award in awards {
+                if is_status!(award, "active")
+                    && let Some(value) = award.get("value")
+                    && let Some(amount) = item.get_amount(self.currency.as_ref(), value)
+                {
+                    let buyer_awards = item.r011_awards.entry(buyer_id.clone()).or_default();
+                    buyer_awards.count += 1;
+
+                    if amount >= lower
+                        && amount < upper
+                        && let Some(Value::String(date)) = award.get("date")
+                        && let Ok(date) = DateTime::parse_from_rfc3339(date)
+                    {
+                        let mut prefixes = HashSet::new();
+                        if let Some(Value::Array(items)) = award.get("items") {
+                            for award_item in items {
+                                if let Some(Value::Object(classification)) = award_item.get("classification")
+                                    && let Some(Value::String(classification_id)) = classification.get("id")
+                                {
+                                    prefixes.insert(classification_id.chars().take(self.digits).collect());
+                                }
+                            }
+                        }
+                        buyer_awards.below.push((date.timestamp(), prefixes, ocid.to_owned()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        for (key, other_awards) in std::mem::take(&mut other.r011_awards) {
+            let buyer_awards = item.r011_awards.entry(key).or_default();
+            buyer_awards.count += other_awards.count;
+            buyer_awards.below.extend(other_awards.below);
+        }
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let window = self.window * 86_400;
+        let mut counts = HashMap::new();
+        let mut shares = HashMap::new();
+
+        for (id, BuyerAwards { count, below }) in std::mem::take(&mut item.r011_awards) {
+            if below.is_empty() {
+                continue;
+            }
+
+            // Sort by prefix, to break ties consistently.
+            let mut timestamps: BTreeMap<&String, Vec<(i64, &String)>> = BTreeMap::new();
+            for (timestamp, prefixes, ocid) in &below {
+                for prefix in prefixes {
+                    timestamps.entry(prefix).or_default().push((*timestamp, ocid));
+                }
+            }
+
+            // The largest number of awards for the same classification within a rolling window.
+            let mut largest: &[(i64, &String)] = &[];
+            for values in timestamps.values_mut() {
+                values.sort_unstable();
+                let mut start = 0;
+                for end in 0..values.len() {
+                    while values[end].0 - values[start].0 > window {
+                        start += 1;
+                    }
+                    if end - start + 1 > largest.len() {
+                        largest = &values[start..=end];
+                    }
+                }
+            }
+
+            let ocids: HashSet<String> = largest.iter().map(|(_, ocid)| (*ocid).clone()).collect();
+            counts.insert(id.clone(), (largest.len() as f64, ocids));
+            shares.insert(id, below.len() as f64 / count as f64);
+        }
+
+        let count_upper_fence = match self.threshold {
+            Some(v) => {
+                set_meta!(item, R011, "count_upper_fence", v as f64);
+                Some(v as f64)
+            }
+            None => upper_fence(item, counts.values().map(|(count, _)| *count).collect(), "count"),
+        };
+        // If the upper fence for the count is configured, the share isn't used.
+        let share_upper_fence = if self.threshold.is_some() {
+            None
+        } else {
+            upper_fence(item, shares.values().copied().collect(), "share")
+        };
+
+        for (id, (count, ocids)) in counts {
+            // A single award isn't split.
+            if count >= 2.0
+                && (count_upper_fence.is_some_and(|fence| count >= fence)
+                    || share_upper_fence.is_some_and(|fence| shares[&id] >= fence))
+            {
+                if item.map {
+                    for ocid in ocids {
+                        item.maps.ocid_buyer_r011.insert(ocid, id.clone());
+                    }
+                }
+                set_result!(item, Buyer, id, R011, count);
+            }
+        }
+    }
+}
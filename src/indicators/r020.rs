@@ -0,0 +1,46 @@
+use chrono::DateTime;
+use serde_json::{Map, Value};
+
+use crate::indicators::{set_result, Calculate, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R020 {}
+
+impl Calculate for R020 {
+    fn new(_settings: &mut Settings) -> Self {
+        Self::default()
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(Value::Object(tender)) = release.get("tender") else {
+            return;
+        };
+
+        let parse_date = |object: &Value| DateTime::parse_from_rfc3339(object.get("date")?.as_str()?).ok();
+
+        // The submission deadline, or the first bid, if earlier.
+        let end_date = tender
+            .get("tenderPeriod")
+            .and_then(|tender_period| DateTime::parse_from_rfc3339(tender_period.get("endDate")?.as_str()?).ok());
+        let first_bid_date = Indicators::get_submitted_bids(release)
+            .into_iter()
+            .filter_map(parse_date)
+            .min();
+        let Some(cutoff) = end_date.into_iter().chain(first_bid_date).min() else {
+            return;
+        };
+
+        // `tender/amendment` is deprecated in OCDS 1.1, in favor of `tender/amendments`.
+        let mut amendments = vec![];
+        if let Some(Value::Array(values)) = tender.get("amendments") {
+            amendments.extend(values);
+        }
+        if let Some(value) = tender.get("amendment") {
+            amendments.push(value);
+        }
+
+        if amendments.into_iter().filter_map(parse_date).any(|date| date > cutoff) {
+            set_result!(item, OCID, ocid, R020, 1.0);
+        }
+    }
+}
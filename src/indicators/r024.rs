@@ -1,7 +1,7 @@
 use statrs::statistics::Data;
 use statrs::statistics::OrderStatistics;
 
-use crate::indicators::{set_meta, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+use crate::indicators::{set_lot_result, set_meta, set_result, set_tenderer_map, Calculate, Indicators, Settings};
 
 #[derive(Default)]
 pub struct R024 {
@@ -35,10 +35,11 @@ impl Calculate for R024 {
         // The percentage difference is always non-negative.
         // Skip if 75% of contracting processes have no percentage difference; otherwise, 75% are flagged.
         if q1 > 0.0 && lower_fence > 0.0 {
-            for (ocid, ratio) in &item.second_lowest_bid_ratios {
+            for (key, ratio) in &item.second_lowest_bid_ratios {
+                let (ocid, _) = key;
                 if *ratio <= lower_fence {
-                    set_result!(item, OCID, ocid, R024, *ratio);
-                    for id in &item.winner_and_lowest_non_winner[ocid] {
+                    set_lot_result!(item, ocid, R024, *ratio, min);
+                    for id in item.winner_and_lowest_non_winner[key].iter().flatten() {
                         set_result!(item, Tenderer, id, R024, 0.0);
                         set_tenderer_map!(item, ocid_tenderer_r024, ocid.clone(), id.clone());
                     }
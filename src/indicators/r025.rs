@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use serde_json::{Map, Value};
 use statrs::statistics::Data;
@@ -20,31 +20,35 @@ impl Calculate for R025 {
     }
 
     fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, _ocid: &str) {
-        if let Some((complete_awards, details)) =
-            Indicators::get_complete_awards_and_bids_if_all_awards_final(release)
+        let Some(competitions) = Indicators::get_competitions_if_all_awards_final(release) else {
+            return;
+        };
+
+        for competition in competitions {
             // See comments for SecondLowestBidRatio.fold().
-            && complete_awards.len() == 1
-            && let Some(Value::Array(suppliers)) = complete_awards[0].get("suppliers")
-            && suppliers.len() == 1
-            && let Some(Value::String(supplier_id)) = suppliers[0].get("id")
-        {
-            let mut valid_tenderer_ids = HashSet::new();
+            if competition.awards.len() == 1
+                && let Some(supplier_ids) = Indicators::get_organization_ids(competition.awards[0], "suppliers")
+            {
+                // Whether each tenderer won. Each member of a consortium is a tenderer.
+                let mut valid_tenderer_ids: HashMap<&String, bool> = HashMap::new();
 
-            for bid in details {
-                if let Some(Value::String(status)) = bid.get("status")
-                    && let Some(Value::Array(tenderers)) = bid.get("tenderers")
-                    && tenderers.len() == 1
-                    && let Some(Value::String(tenderer_id)) = tenderers[0].get("id")
-                    && status == "valid"
-                {
-                    valid_tenderer_ids.insert(tenderer_id);
+                for bid in &competition.bids {
+                    if let Some(Value::String(status)) = bid.get("status")
+                        && let Some(tenderer_ids) = Indicators::get_organization_ids(bid, "tenderers")
+                        && status == "valid"
+                    {
+                        let won = tenderer_ids == supplier_ids;
+                        for tenderer_id in tenderer_ids {
+                            *valid_tenderer_ids.entry(tenderer_id).or_default() |= won;
+                        }
+                    }
                 }
-            }
 
-            // Count each tenderer once per contracting process, regardless of the number of bids.
-            for tenderer_id in valid_tenderer_ids {
-                let fraction = item.r025_tenderer.entry(tenderer_id.clone()).or_default();
-                *fraction += fraction!((supplier_id == tenderer_id).into(), 1);
+                // Count each tenderer once per lot, regardless of the number of bids.
+                for (tenderer_id, won) in valid_tenderer_ids {
+                    let fraction = item.r025_tenderer.entry(tenderer_id.clone()).or_default();
+                    *fraction += fraction!(won.into(), 1);
+                }
             }
         }
     }
@@ -0,0 +1,127 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{set_meta, set_result, sum, Calculate, CoBids, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R029 {
+    threshold: Option<usize>, // resolved in finalize()
+    minimum_contracting_processes: usize,
+}
+
+impl Calculate for R029 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R029).unwrap_or_default();
+
+        Self {
+            threshold: setting.threshold,
+            minimum_contracting_processes: setting.minimum_contracting_processes.unwrap_or(2),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(complete_awards) = Indicators::get_complete_awards_if_all_awards_final(release) else {
+            return;
+        };
+
+        for competition in Indicators::split_by_lot(release, complete_awards, Indicators::get_submitted_bids(release)) {
+            let mut supplier_ids = HashSet::new();
+            for award in &competition.awards {
+                if let Some(ids) = Indicators::get_organization_ids(award, "suppliers") {
+                    supplier_ids.extend(ids);
+                }
+            }
+
+            // The winners must be known, in order to detect rotation.
+            if supplier_ids.is_empty() {
+                continue;
+            }
+
+            let bids: Vec<BTreeSet<&String>> = competition
+                .bids
+                .iter()
+                .filter_map(|bid| Indicators::get_organization_ids(bid, "tenderers"))
+                .collect();
+            let tenderer_ids: BTreeSet<&String> = bids.iter().flatten().copied().collect();
+
+            for (i, a) in tenderer_ids.iter().enumerate() {
+                for b in tenderer_ids.iter().skip(i + 1) {
+                    // Members of the same consortium don't compete with each other.
+                    if bids.iter().any(|ids| ids.contains(a) && ids.contains(b)) {
+                        continue;
+                    }
+
+                    let co_bids = item.r029_pairs.entry(((*a).clone(), (*b).clone())).or_default();
+                    co_bids.count += 1;
+                    co_bids.wins[0] += usize::from(supplier_ids.contains(a));
+                    co_bids.wins[1] += usize::from(supplier_ids.contains(b));
+                    if item.map {
+                        co_bids.ocids.insert(ocid.to_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        sum!(item, other, r029_pairs);
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let pairs: HashMap<(String, String), CoBids> = std::mem::take(&mut item.r029_pairs)
+            .into_iter()
+            .filter(|(_, co_bids)| co_bids.count >= self.minimum_contracting_processes)
+            .collect();
+
+        if pairs.is_empty() {
+            return;
+        }
+
+        let upper_fence = self.threshold.map_or_else(
+            || {
+                let mut data = Data::new(pairs.values().map(|co_bids| co_bids.count as f64).collect::<Vec<_>>());
+                let q1 = data.lower_quartile();
+                let q3 = data.upper_quartile();
+                set_meta!(item, R029, "q1", q1);
+                set_meta!(item, R029, "q3", q3);
+                // q3 + IQR * 1.5
+                (q3 - q1).mul_add(1.5, q3)
+            },
+            |v| v as f64,
+        );
+
+        set_meta!(item, R029, "upper_fence", upper_fence);
+
+        // A tenderer can be in many pairs. Sort for consistent output.
+        let mut counts: BTreeMap<String, f64> = BTreeMap::new();
+
+        for (pair, co_bids) in pairs {
+            let count = co_bids.count as f64;
+            // Both tenderers win some of the competitions in which they bid together.
+            if count >= upper_fence && co_bids.wins[0] > 0 && co_bids.wins[1] > 0 {
+                for id in <[String; 2]>::from(pair) {
+                    if item.map {
+                        for ocid in &co_bids.ocids {
+                            item.maps
+                                .ocid_tenderer_r029
+                                .entry(ocid.clone())
+                                .or_default()
+                                .insert(id.clone());
+                        }
+                    }
+                    counts
+                        .entry(id)
+                        .and_modify(|value| *value = value.max(count))
+                        .or_insert(count);
+                }
+            }
+        }
+
+        for (id, count) in counts {
+            set_result!(item, Tenderer, id, R029, count);
+        }
+    }
+}
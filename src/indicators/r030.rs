@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use chrono::DateTime;
 use serde_json::{Map, Value};
 
 use crate::indicators::{reduce_map, set_result, set_tenderer_map, Calculate, Indicators, Settings};
@@ -16,6 +17,7 @@ impl Calculate for R030 {
         if let Some(Value::Object(tender)) = release.get("tender")
             && let Some(Value::Object(tender_period)) = tender.get("tenderPeriod")
             && let Some(Value::String(end_date)) = tender_period.get("endDate")
+            && let Ok(end_date) = DateTime::parse_from_rfc3339(end_date)
             && let Some(Value::Array(awards)) = release.get("awards")
             && let Some(Value::Object(bids)) = release.get("bids")
             && let Some(Value::Array(details)) = bids.get("details")
@@ -40,6 +42,7 @@ impl Calculate for R030 {
                     && let Some(Value::String(date)) = bid.get("date")
                     && let Some(Value::Array(tenderers)) = bid.get("tenderers")
                     && status == "valid"
+                    && let Ok(date) = DateTime::parse_from_rfc3339(date)
                     && date > end_date
                 {
                     for tenderer in tenderers {
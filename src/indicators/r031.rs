@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::DateTime;
+use log::warn;
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{is_status, set_meta, set_result, Calculate, Indicators, Settings};
+use crate::parse_pipe_separated_value;
+
+#[derive(Default)]
+pub struct R031 {
+    procurement_methods: HashSet<String>,
+    procurement_method_details: HashMap<String, [Option<f64>; 2]>, // resolved in finalize()
+}
+
+// Parses a "lower|upper" value, in which either fence can be blank.
+fn parse_fences(value: &str) -> Option<[Option<f64>; 2]> {
+    let (lower, upper) = value.split_once('|')?;
+    let parse = |fence: &str| match fence.trim() {
+        "" => Ok(None),
+        fence => fence.parse::<u32>().map(|v| Some(f64::from(v))),
+    };
+
+    Some([parse(lower).ok()?, parse(upper).ok()?])
+}
+
+impl Calculate for R031 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R031).unwrap_or_default();
+
+        Self {
+            procurement_methods: parse_pipe_separated_value(setting.procurement_methods),
+            procurement_method_details: setting
+                .procurement_method_details
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(details, value)| {
+                    let fences = parse_fences(&value);
+                    if fences.is_none() {
+                        warn!("R031.procurement_method_details: {details} is not like 5|60, skipping.");
+                    }
+                    Some((details, fences?))
+                })
+                .collect(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        if let Some(Value::Object(tender)) = release.get("tender")
+            && Indicators::matches_procurement_method(tender, &self.procurement_methods)
+            && let Some(Value::Object(tender_period)) = tender.get("tenderPeriod")
+            && let Some(Value::String(end_date)) = tender_period.get("endDate")
+            && let Ok(end_date) = DateTime::parse_from_rfc3339(end_date)
+            && let Some(Value::Array(awards)) = release.get("awards")
+        {
+            // The evaluation ends with the first award.
+            let award_date = awards
+                .iter()
+                .filter(|award| is_status!(award, "active"))
+                .filter_map(|award| DateTime::parse_from_rfc3339(award.get("date")?.as_str()?).ok())
+                .min();
+
+            if let Some(award_date) = award_date
+                && let duration = (award_date - end_date).num_days()
+                // An award before the submission deadline is a data quality issue.
+                && duration >= 0
+            {
+                let details = match tender.get("procurementMethodDetails") {
+                    Some(Value::String(details)) => details.clone(),
+                    _ => String::new(),
+                };
+                item.r031_durations
+                    .entry(details)
+                    .or_default()
+                    .insert(ocid.to_owned(), duration as f64);
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        for (details, durations) in std::mem::take(&mut other.r031_durations) {
+            // If each OCID appears on one line of the file, no overwriting occurs.
+            item.r031_durations.entry(details).or_default().extend(durations);
+        }
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        for (details, durations) in std::mem::take(&mut item.r031_durations) {
+            let prefix = if details.is_empty() {
+                String::new()
+            } else {
+                format!("{details}_")
+            };
+            let [lower, upper] = self
+                .procurement_method_details
+                .get(&details)
+                .copied()
+                .unwrap_or_default();
+
+            // Only calculate the quartiles if a fence isn't configured.
+            let (lower_fence, upper_fence) = if let (Some(lower), Some(upper)) = (lower, upper) {
+                (lower, upper)
+            } else {
+                let mut data = Data::new(durations.values().copied().collect::<Vec<_>>());
+                let q1 = data.lower_quartile();
+                let q3 = data.upper_quartile();
+                set_meta!(item, R031, format!("{prefix}q1"), q1);
+                set_meta!(item, R031, format!("{prefix}q3"), q3);
+                let iqr = q3 - q1;
+                (
+                    // q1 - IQR * 1.5
+                    lower.unwrap_or_else(|| iqr.mul_add(-1.5, q1)),
+                    // q3 + IQR * 1.5
+                    upper.unwrap_or_else(|| iqr.mul_add(1.5, q3)),
+                )
+            };
+
+            set_meta!(item, R031, format!("{prefix}lower_fence"), lower_fence);
+            set_meta!(item, R031, format!("{prefix}upper_fence"), upper_fence);
+
+            for (ocid, duration) in durations {
+                if duration < lower_fence || duration > upper_fence {
+                    set_result!(item, OCID, ocid, R031, duration);
+                }
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::HashSet;
 
 use serde_json::{Map, Value};
 
-use crate::indicators::{reduce_map, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+use crate::indicators::{reduce_map, set_lot_result, set_result, set_tenderer_map, Calculate, Indicators, Settings};
 
 #[derive(Default)]
 pub struct R035 {
@@ -18,13 +18,16 @@ impl Calculate for R035 {
     }
 
     fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
-        let mut award_supplier_ids = HashSet::new();
-        let mut valid_tenderer_ids = HashSet::new();
-        let mut disqualified_tenderer_ids = HashSet::new();
+        let Some(competitions) = Indicators::get_competitions_if_all_awards_final(release) else {
+            return;
+        };
 
-        if let Some((complete_awards, details)) = Indicators::get_complete_awards_and_bids_if_all_awards_final(release)
-        {
-            for award in complete_awards {
+        for competition in competitions {
+            let mut award_supplier_ids = HashSet::new();
+            let mut valid_tenderer_ids = HashSet::new();
+            let mut disqualified_tenderer_ids = HashSet::new();
+
+            for award in &competition.awards {
                 if let Some(Value::Array(suppliers)) = award.get("suppliers") {
                     for supplier in suppliers {
                         if let Some(Value::String(id)) = supplier.get("id") {
@@ -34,7 +37,7 @@ impl Calculate for R035 {
                 }
             }
 
-            for bid in details {
+            for bid in &competition.bids {
                 if let Some(Value::String(status)) = bid.get("status")
                     && let Some(Value::Array(tenderers)) = bid.get("tenderers")
                 {
@@ -51,22 +54,22 @@ impl Calculate for R035 {
                     }
                 }
             }
-        }
 
-        // R035 is not applicable to multiple tenderers/winners. A buyer can aggregate multiple bids
-        // into one award, and then sign multiple contracts. That behavior is not a red flag.
-        if valid_tenderer_ids.len() == 1
-            // The tenderer's bids were awarded.
-            && valid_tenderer_ids == award_supplier_ids
-            // Others' bids were disqualified.
-            && let difference = disqualified_tenderer_ids.difference(&valid_tenderer_ids).count()
-            // At least this many tenderers have disqualified bids.
-            && difference >= self.threshold
-        {
-            set_result!(item, OCID, ocid, R035, difference as f64);
-            let id = valid_tenderer_ids.iter().next().unwrap().to_owned();
-            set_result!(item, Tenderer, id, R035, 0.0);
-            set_tenderer_map!(item, ocid_tenderer_r035, ocid.to_owned(), id.clone());
+            // R035 is not applicable to multiple tenderers/winners. A buyer can aggregate multiple bids
+            // into one award, and then sign multiple contracts. That behavior is not a red flag.
+            if valid_tenderer_ids.len() == 1
+                // The tenderer's bids were awarded.
+                && valid_tenderer_ids == award_supplier_ids
+                // Others' bids were disqualified.
+                && let difference = disqualified_tenderer_ids.difference(&valid_tenderer_ids).count()
+                // At least this many tenderers have disqualified bids.
+                && difference >= self.threshold
+            {
+                set_lot_result!(item, ocid, R035, difference as f64, max);
+                let id = valid_tenderer_ids.iter().next().unwrap().to_owned();
+                set_result!(item, Tenderer, id, R035, 0.0);
+                set_tenderer_map!(item, ocid_tenderer_r035, ocid.to_owned(), id.clone());
+            }
         }
     }
 
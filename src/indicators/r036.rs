@@ -35,47 +35,57 @@ impl Calculate for R036 {
             return;
         }
 
-        let mut lowest_amount = None;
-        let mut lowest_amount_is_disqualified = false;
-        let mut has_valid_bid_with_amount = false;
+        let Some(Value::Array(awards)) = release.get("awards") else {
+            return;
+        };
+
+        let competitions = Indicators::split_by_lot(
+            release,
+            awards.iter().collect(),
+            Indicators::get_submitted_bids(release),
+        );
+
+        for competition in competitions {
+            let mut lowest_amount = None;
+            let mut lowest_amount_is_disqualified = false;
+            let mut has_valid_bid_with_amount = false;
 
-        if let Some(Value::Array(awards)) = release.get("awards")
             // There are one or more complete awards.
-            && awards.iter().any(|award| is_status!(award, "active"))
-        {
-            for bid in Indicators::get_submitted_bids(release) {
-                if let Some(Value::String(status)) = bid.get("status")
-                    && let Some(Value::Object(value)) = bid.get("value")
-                    && let Some(Value::Number(amount)) = value.get("amount")
-                    && let Some(Value::String(currency)) = value.get("currency")
-                    && let Some(amount) = amount.as_f64()
-                {
-                    if currency
-                        == item.currency.get_or_insert_with(|| {
-                            self.currency.as_ref().map_or_else(|| currency.clone(), Clone::clone)
-                        })
+            if competition.awards.iter().any(|award| is_status!(award, "active")) {
+                for bid in &competition.bids {
+                    if let Some(Value::String(status)) = bid.get("status")
+                        && let Some(Value::Object(value)) = bid.get("value")
+                        && let Some(Value::Number(amount)) = value.get("amount")
+                        && let Some(Value::String(currency)) = value.get("currency")
+                        && let Some(amount) = amount.as_f64()
                     {
-                        if let Some(other) = lowest_amount {
-                            if amount < other {
+                        if currency
+                            == item.currency.get_or_insert_with(|| {
+                                self.currency.as_ref().map_or_else(|| currency.clone(), Clone::clone)
+                            })
+                        {
+                            if let Some(other) = lowest_amount {
+                                if amount < other {
+                                    lowest_amount = Some(amount);
+                                    lowest_amount_is_disqualified = status == "disqualified";
+                                }
+                            } else {
                                 lowest_amount = Some(amount);
                                 lowest_amount_is_disqualified = status == "disqualified";
                             }
+                            if status == "valid" {
+                                has_valid_bid_with_amount = true;
+                            }
                         } else {
-                            lowest_amount = Some(amount);
-                            lowest_amount_is_disqualified = status == "disqualified";
-                        }
-                        if status == "valid" {
-                            has_valid_bid_with_amount = true;
+                            warn!("{} is not {:?}, skipping.", currency, item.currency);
                         }
-                    } else {
-                        warn!("{} is not {:?}, skipping.", currency, item.currency);
                     }
                 }
             }
-        }
 
-        if has_valid_bid_with_amount && lowest_amount_is_disqualified {
-            set_result!(item, OCID, ocid, R036, 1.0);
+            if has_valid_bid_with_amount && lowest_amount_is_disqualified {
+                set_result!(item, OCID, ocid, R036, 1.0);
+            }
         }
     }
 }
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::indicators::{is_status, reduce_map, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+
+/// A row of the CSV file of registration dates.
+#[derive(Deserialize)]
+struct Row {
+    /// A `parties/identifier/id` value.
+    identifier: String,
+    /// A date like "2024-01-31".
+    date: String,
+}
+
+#[derive(Default)]
+pub struct R043 {
+    threshold: i64,
+    /// The registration date for each `parties/identifier/id`.
+    registration_dates: HashMap<String, NaiveDate>,
+}
+
+impl R043 {
+    /// Initialize the indicator, reading registration dates from a CSV file with the columns: identifier, date.
+    ///
+    /// # Errors
+    ///
+    pub fn try_new(settings: &mut Settings) -> Result<Self> {
+        let path = settings
+            .R043
+            .as_mut()
+            .and_then(|setting| setting.registration_dates.take());
+        let mut indicator = Self::new(settings);
+
+        if let Some(path) = path {
+            let mut reader = csv::Reader::from_path(&path).with_context(|| format!("registration_dates: {path}"))?;
+            for (i, result) in reader.deserialize().enumerate() {
+                let row: Row = result.with_context(|| format!("registration_dates: {path}"))?;
+                match row.date.trim().parse() {
+                    Ok(date) => {
+                        indicator.registration_dates.insert(row.identifier, date);
+                    }
+                    Err(e) => bail!("registration_dates: {path}: row {} has an invalid date [{e}]", i + 2),
+                }
+            }
+        }
+
+        Ok(indicator)
+    }
+
+    // Returns the registration date from the party's details, or else from the CSV file.
+    fn get_registration_date(&self, party: &Value) -> Option<NaiveDate> {
+        if let Some(Value::Object(details)) = party.get("details")
+            && let Some(Value::String(date)) = details.get("registrationDate")
+            && let Some(date) = parse_date(date)
+        {
+            return Some(date);
+        }
+
+        if let Some(Value::Object(identifier)) = party.get("identifier")
+            && let Some(Value::String(id)) = identifier.get("id")
+        {
+            return self.registration_dates.get(id).copied();
+        }
+
+        None
+    }
+}
+
+// A registration date can be a date-time or a date.
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.date_naive())
+        .or_else(|_| date.parse())
+        .ok()
+}
+
+impl Calculate for R043 {
+    fn new(settings: &mut Settings) -> Self {
+        Self {
+            threshold: std::mem::take(&mut settings.R043)
+                .and_then(|v| v.threshold)
+                .map_or(365, i64::from),
+            ..Default::default()
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let (Some(Value::Array(parties)), Some(Value::Array(awards))) = (release.get("parties"), release.get("awards"))
+        else {
+            return;
+        };
+
+        let registration_dates: HashMap<&String, NaiveDate> = parties
+            .iter()
+            .filter_map(|party| match party.get("id") {
+                Some(Value::String(id)) => Some((id, self.get_registration_date(party)?)),
+                _ => None,
+            })
+            .collect();
+
+        let mut youngest: Option<f64> = None;
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(Value::String(date)) = award.get("date")
+                && let Ok(award_date) = DateTime::parse_from_rfc3339(date)
+                && let Some(Value::Array(suppliers)) = award.get("suppliers")
+            {
+                for supplier in suppliers {
+                    if let Some(Value::String(id)) = supplier.get("id")
+                        && let Some(registration_date) = registration_dates.get(id)
+                        && let age = (award_date.date_naive() - *registration_date).num_days()
+                        // A supplier registered after its award is a data quality issue.
+                        && age >= 0
+                        && age < self.threshold
+                    {
+                        let age = age as f64;
+                        youngest = Some(youngest.map_or(age, |value| value.min(age)));
+                        item.r043_ages
+                            .entry(id.clone())
+                            .and_modify(|value| *value = value.min(age))
+                            .or_insert(age);
+                        set_tenderer_map!(item, ocid_tenderer_r043, ocid.to_owned(), id.clone());
+                    }
+                }
+            }
+        }
+
+        if let Some(age) = youngest {
+            set_result!(item, OCID, ocid, R043, age);
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        for (id, other_age) in std::mem::take(&mut other.r043_ages) {
+            item.r043_ages
+                .entry(id)
+                .and_modify(|value| *value = value.min(other_age))
+                .or_insert(other_age);
+        }
+
+        reduce_map!(item, other, ocid_tenderer_r043);
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        for (id, age) in std::mem::take(&mut item.r043_ages) {
+            set_result!(item, Tenderer, id, R043, age);
+        }
+    }
+}
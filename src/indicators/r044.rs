@@ -0,0 +1,124 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde_json::{Map, Value};
+
+use crate::indicators::{reduce_map, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R044 {}
+
+// Returns the lowercase alphanumeric characters of the strings, or None if there are none.
+fn normalize(values: &[Option<&Value>]) -> Option<String> {
+    let normalized: String = values
+        .iter()
+        .filter_map(|value| value.and_then(Value::as_str))
+        .flat_map(str::chars)
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+// Returns the normalized contact details of the party.
+fn get_contact_details(party: &Value) -> Vec<(&'static str, String)> {
+    let mut details = vec![];
+
+    if let Some(address) = party.get("address")
+        && address.get("streetAddress").is_some()
+        && let Some(value) = normalize(&[
+            address.get("streetAddress"),
+            address.get("locality"),
+            address.get("postalCode"),
+        ])
+    {
+        details.push(("address", value));
+    }
+
+    if let Some(contact_point) = party.get("contactPoint") {
+        if let Some(Value::String(telephone)) = contact_point.get("telephone") {
+            let digits: String = telephone.chars().filter(char::is_ascii_digit).collect();
+            if !digits.is_empty() {
+                details.push(("telephone", digits));
+            }
+        }
+        if let Some(Value::String(email)) = contact_point.get("email") {
+            let email = email.trim().to_lowercase();
+            if !email.is_empty() {
+                details.push(("email", email));
+            }
+        }
+        if let Some(value) = normalize(&[contact_point.get("name")]) {
+            details.push(("contact_name", value));
+        }
+    }
+
+    if let Some(identifier) = party.get("identifier")
+        && identifier.get("id").is_some()
+        && let Some(value) = normalize(&[identifier.get("scheme"), identifier.get("id")])
+    {
+        details.push(("identifier", value));
+    }
+
+    details
+}
+
+impl Calculate for R044 {
+    fn new(_settings: &mut Settings) -> Self {
+        Self::default()
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(Value::Array(parties)) = release.get("parties") else {
+            return;
+        };
+
+        let bids: Vec<BTreeSet<&String>> = Indicators::get_submitted_bids(release)
+            .into_iter()
+            .filter_map(|bid| Indicators::get_organization_ids(bid, "tenderers"))
+            .collect();
+        let tenderer_ids: BTreeSet<&String> = bids.iter().flatten().copied().collect();
+
+        // The tenderers for each normalized contact detail.
+        let mut tenderers: HashMap<(&str, String), BTreeSet<&String>> = HashMap::new();
+
+        for party in parties {
+            if let Some(Value::String(id)) = party.get("id")
+                && let Some(tenderer_id) = tenderer_ids.get(id)
+            {
+                for detail in get_contact_details(party) {
+                    tenderers.entry(detail).or_default().insert(tenderer_id);
+                }
+            }
+        }
+
+        for ((attribute, _), ids) in tenderers {
+            // Members of the same consortium are expected to share contact details.
+            if ids.len() < 2 || bids.iter().any(|bid_tenderer_ids| ids.is_subset(bid_tenderer_ids)) {
+                continue;
+            }
+
+            set_result!(item, OCID, ocid, R044, 1.0);
+            if item.map {
+                item.maps
+                    .ocid_attribute_r044
+                    .entry(ocid.to_owned())
+                    .or_default()
+                    .insert(attribute.to_owned());
+            }
+            for id in ids {
+                set_result!(item, Tenderer, id, R044, 1.0);
+                set_tenderer_map!(item, ocid_tenderer_r044, ocid.to_owned(), id.clone());
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        reduce_map!(item, other, ocid_attribute_r044);
+        reduce_map!(item, other, ocid_tenderer_r044);
+    }
+}
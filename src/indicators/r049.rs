@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{is_status, set_meta, set_result, BuyerSuppliers, Calculate, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R049 {
+    threshold: Option<f64>, // resolved in finalize()
+    minimum_contracting_processes: usize,
+    currency: Option<String>,
+}
+
+impl Calculate for R049 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R049).unwrap_or_default();
+
+        Self {
+            threshold: setting.threshold,
+            minimum_contracting_processes: setting.minimum_contracting_processes.unwrap_or(20),
+            currency: settings.currency.clone(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let (Some(Value::Object(buyer)), Some(Value::Array(awards))) = (release.get("buyer"), release.get("awards"))
+        else {
+            return;
+        };
+        let Some(Value::String(buyer_id)) = buyer.get("id") else {
+            return;
+        };
+
+        let mut observed = false;
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(Value::Array(suppliers)) = award.get("suppliers")
+                // Don't assume that suppliers share the award's value equally.
+                && suppliers.len() == 1
+                && let Some(Value::String(supplier_id)) = suppliers[0].get("id")
+                && let Some(value) = award.get("value")
+                && let Some(amount) = item.get_amount(self.currency.as_ref(), value)
+            {
+                let map = item.map;
+                let buyer_suppliers = item.r049_buyers.entry(buyer_id.clone()).or_default();
+                let (total, ocids) = buyer_suppliers.suppliers.entry(supplier_id.clone()).or_default();
+                *total += amount;
+                // A contracting process's awards are folded together.
+                if map && ocids.last().is_none_or(|last| last != ocid) {
+                    ocids.push(ocid.to_owned());
+                }
+                observed = true;
+            }
+        }
+
+        // A buyer is observed in a contracting process (ocid) at most once.
+        if observed {
+            item.r049_buyers.entry(buyer_id.clone()).or_default().count += 1;
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        for (key, other_buyer) in std::mem::take(&mut other.r049_buyers) {
+            let buyer_suppliers = item.r049_buyers.entry(key).or_default();
+            buyer_suppliers.count += other_buyer.count;
+            for (supplier_id, (other_total, other_ocids)) in other_buyer.suppliers {
+                let (total, ocids) = buyer_suppliers.suppliers.entry(supplier_id).or_default();
+                *total += other_total;
+                ocids.extend(other_ocids);
+            }
+        }
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let mut shares = vec![];
+
+        for (id, BuyerSuppliers { count, suppliers }) in std::mem::take(&mut item.r049_buyers) {
+            let total: f64 = suppliers.values().map(|(amount, _)| amount).sum();
+            if count < self.minimum_contracting_processes || total <= 0.0 {
+                continue;
+            }
+
+            // Break ties by identifier, for consistent output.
+            if let Some((supplier_id, (amount, ocids))) = suppliers
+                .into_iter()
+                .max_by(|(a_id, (a, _)), (b_id, (b, _))| a.total_cmp(b).then_with(|| b_id.cmp(a_id)))
+            {
+                shares.push((id, supplier_id, amount / total, ocids));
+            }
+        }
+
+        if shares.is_empty() {
+            return;
+        }
+
+        let upper_fence = self.threshold.unwrap_or_else(|| {
+            let mut data = Data::new(shares.iter().map(|(_, _, share, _)| *share).collect::<Vec<_>>());
+            let q1 = data.lower_quartile();
+            let q3 = data.upper_quartile();
+            set_meta!(item, R049, "q1", q1);
+            set_meta!(item, R049, "q3", q3);
+            // q3 + IQR * 1.5
+            (q3 - q1).mul_add(1.5, q3)
+        });
+
+        set_meta!(item, R049, "upper_fence", upper_fence);
+
+        // A supplier can be dominant for many buyers.
+        let mut tenderer_shares: HashMap<String, f64> = HashMap::new();
+
+        for (id, supplier_id, share, ocids) in shares {
+            if share >= upper_fence {
+                set_result!(item, Buyer, id, R049, share);
+                tenderer_shares
+                    .entry(supplier_id.clone())
+                    .and_modify(|value| *value = value.max(share))
+                    .or_insert(share);
+
+                if item.map {
+                    for ocid in ocids {
+                        item.maps.ocid_buyer_r049.insert(ocid.clone(), id.clone());
+                        item.maps
+                            .ocid_tenderer_r049
+                            .entry(ocid)
+                            .or_default()
+                            .insert(supplier_id.clone());
+                    }
+                }
+            }
+        }
+
+        for (id, share) in tenderer_shares {
+            set_result!(item, Tenderer, id, R049, share);
+        }
+    }
+}
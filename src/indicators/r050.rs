@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::DateTime;
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{is_status, set_meta, set_result, sum, Calculate, Indicators, Settings};
+use crate::{parse_pipe_separated_value, parse_pipe_separated_value_with_default};
+
+#[derive(Default)]
+pub struct R050 {
+    procurement_methods: HashSet<String>,
+    procurement_method_details: HashSet<String>,
+    window: i64,
+    threshold: Option<usize>, // resolved in finalize()
+    minimum_contracting_processes: usize,
+}
+
+impl Calculate for R050 {
+    fn new(settings: &mut Settings) -> Self {
+        let setting = std::mem::take(&mut settings.R050).unwrap_or_default();
+
+        Self {
+            procurement_methods: parse_pipe_separated_value_with_default(
+                setting.procurement_methods,
+                String::from("limited"),
+            ),
+            procurement_method_details: parse_pipe_separated_value(setting.procurement_method_details),
+            window: i64::from(setting.window.unwrap_or(365)),
+            threshold: setting.threshold,
+            minimum_contracting_processes: setting.minimum_contracting_processes.unwrap_or(2),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let Some(Value::Object(tender)) = release.get("tender") else {
+            return;
+        };
+
+        // A non-competitive award has a matching procurement method or procurement method details.
+        let details = tender.get("procurementMethodDetails").and_then(Value::as_str);
+        let procurement_method = tender.get("procurementMethod").and_then(Value::as_str);
+        if !details.is_some_and(|details| self.procurement_method_details.contains(details))
+            && !procurement_method.is_some_and(|method| self.procurement_methods.contains(method))
+        {
+            return;
+        }
+
+        let Some(Value::Object(buyer)) = release.get("buyer") else {
+            return;
+        };
+        let Some(Value::String(buyer_id)) = buyer.get("id") else {
+            return;
+        };
+        let Some(Value::Array(awards)) = release.get("awards") else {
+            return;
+        };
+
+        // A supplier can have many awards in the same contracting process.
+        let mut timestamps: HashMap<&String, i64> = HashMap::new();
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(Value::String(date)) = award.get("date")
+                && let Ok(date) = DateTime::parse_from_rfc3339(date)
+                && let Some(Value::Array(suppliers)) = award.get("suppliers")
+            {
+                let timestamp = date.timestamp();
+                for supplier in suppliers {
+                    if let Some(Value::String(id)) = supplier.get("id") {
+                        timestamps
+                            .entry(id)
+                            .and_modify(|other| *other = (*other).min(timestamp))
+                            .or_insert(timestamp);
+                    }
+                }
+            }
+        }
+
+        for (supplier_id, timestamp) in timestamps {
+            let awards = item
+                .r050_pairs
+                .entry((buyer_id.clone(), supplier_id.clone()))
+                .or_default();
+            awards.timestamps.push(timestamp);
+            if item.map {
+                awards.ocids.insert(ocid.to_owned());
+            }
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        sum!(item, other, r050_pairs);
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let window = self.window * 86_400;
+        let mut counts = HashMap::new();
+
+        for (pair, mut awards) in std::mem::take(&mut item.r050_pairs) {
+            // The largest number of contracting processes within a rolling window.
+            let mut largest = 0;
+            awards.timestamps.sort_unstable();
+            let mut start = 0;
+            for end in 0..awards.timestamps.len() {
+                while awards.timestamps[end] - awards.timestamps[start] > window {
+                    start += 1;
+                }
+                largest = largest.max(end - start + 1);
+            }
+
+            if largest >= self.minimum_contracting_processes {
+                counts.insert(pair, (largest as f64, awards.ocids));
+            }
+        }
+
+        if counts.is_empty() {
+            return;
+        }
+
+        let upper_fence = self.threshold.map_or_else(
+            || {
+                let mut data = Data::new(counts.values().map(|(count, _)| *count).collect::<Vec<_>>());
+                let q1 = data.lower_quartile();
+                let q3 = data.upper_quartile();
+                set_meta!(item, R050, "q1", q1);
+                set_meta!(item, R050, "q3", q3);
+                // q3 + IQR * 1.5
+                (q3 - q1).mul_add(1.5, q3)
+            },
+            |v| v as f64,
+        );
+
+        set_meta!(item, R050, "upper_fence", upper_fence);
+
+        // An organization can be in many pairs. Sort for consistent output.
+        let mut buyers: BTreeMap<String, f64> = BTreeMap::new();
+        let mut tenderers: BTreeMap<String, f64> = BTreeMap::new();
+
+        for ((buyer_id, supplier_id), (count, ocids)) in counts {
+            if count >= upper_fence {
+                if item.map {
+                    for ocid in ocids {
+                        item.maps.ocid_buyer_r050.insert(ocid.clone(), buyer_id.clone());
+                        item.maps
+                            .ocid_tenderer_r050
+                            .entry(ocid)
+                            .or_default()
+                            .insert(supplier_id.clone());
+                    }
+                }
+                for (map, id) in [(&mut buyers, buyer_id), (&mut tenderers, supplier_id)] {
+                    map.entry(id)
+                        .and_modify(|value| *value = value.max(count))
+                        .or_insert(count);
+                }
+            }
+        }
+
+        for (id, count) in buyers {
+            set_result!(item, Buyer, id, R050, count);
+        }
+        for (id, count) in tenderers {
+            set_result!(item, Tenderer, id, R050, count);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use serde_json::{Map, Value};
+
+use crate::indicators::{is_status, set_result, Calculate, Indicators, Settings};
+use crate::parse_pipe_separated_value;
+
+#[derive(Default)]
+pub struct R055 {
+    threshold: f64,
+    no_price_comparison_procurement_methods: HashSet<String>,
+    price_comparison_procurement_methods: HashSet<String>,
+    currency: Option<String>,
+}
+
+impl R055 {
+    // Returns the total amount of the active awards. If an award has no amount, the amount of the winning bid is
+    // used: that is, the lowest valid bid whose tenderers are the award's suppliers.
+    fn get_winning_amount(
+        &self,
+        item: &mut Indicators,
+        complete_awards: &[&Value],
+        release: &Map<String, Value>,
+    ) -> Option<f64> {
+        let mut total = 0.0;
+
+        for award in complete_awards {
+            if let Some(value) = award.get("value")
+                && let Some(amount) = item.get_amount(self.currency.as_ref(), value)
+            {
+                total += amount;
+                continue;
+            }
+
+            let supplier_ids = Indicators::get_organization_ids(award, "suppliers")?;
+            let mut lowest_amount = None;
+
+            for bid in Indicators::get_submitted_bids(release) {
+                if is_status!(bid, "valid")
+                    && Indicators::get_organization_ids(bid, "tenderers").as_ref() == Some(&supplier_ids)
+                    && let Some(value) = bid.get("value")
+                    && let Some(amount) = item.get_amount(self.currency.as_ref(), value)
+                    && lowest_amount.is_none_or(|other| amount < other)
+                {
+                    lowest_amount = Some(amount);
+                }
+            }
+
+            total += lowest_amount?;
+        }
+
+        Some(total)
+    }
+}
+
+impl Calculate for R055 {
+    fn new(settings: &mut Settings) -> Self {
+        Self {
+            threshold: std::mem::take(&mut settings.R055)
+                .and_then(|v| v.threshold)
+                .unwrap_or(0.05),
+            no_price_comparison_procurement_methods: parse_pipe_separated_value(
+                settings.no_price_comparison_procurement_methods.clone(),
+            ),
+            price_comparison_procurement_methods: parse_pipe_separated_value(
+                settings.price_comparison_procurement_methods.clone(),
+            ),
+            currency: settings.currency.clone(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        if !Indicators::matches_procurement_method_details(
+            release,
+            &self.price_comparison_procurement_methods,
+            &self.no_price_comparison_procurement_methods,
+        ) {
+            return;
+        }
+
+        if let Some(Value::Object(tender)) = release.get("tender")
+            && let Some(value) = tender.get("value")
+            && let Some(estimate) = item.get_amount(self.currency.as_ref(), value)
+            && estimate > 0.0
+            && let Some(complete_awards) = Indicators::get_complete_awards_if_all_awards_final(release)
+            // There are one or more complete awards.
+            && !complete_awards.is_empty()
+            && let Some(amount) = self.get_winning_amount(item, &complete_awards, release)
+            // The difference is negative if the winning amount exceeds the estimate.
+            && let difference = (estimate - amount) / estimate
+            && difference <= self.threshold
+        {
+            set_result!(item, OCID, ocid, R055, difference);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use statrs::statistics::Data;
 use statrs::statistics::OrderStatistics;
 
-use crate::indicators::{set_meta, set_result, set_tenderer_map, Calculate, Indicators, Settings};
+use crate::indicators::{set_lot_result, set_meta, set_result, set_tenderer_map, Calculate, Indicators, Settings};
 
 #[derive(Default)]
 pub struct R058 {
@@ -32,12 +32,14 @@ impl Calculate for R058 {
         // The percentage difference is always non-negative.
         // Skip if 75% of contracting processes have no percentage difference; otherwise, 75% are flagged.
         if upper_fence > 0.0 {
-            for (ocid, ratio) in &item.second_lowest_bid_ratios {
+            for (key, ratio) in &item.second_lowest_bid_ratios {
+                let (ocid, _) = key;
                 if *ratio >= upper_fence {
-                    set_result!(item, OCID, ocid, R058, *ratio);
-                    let id = &item.winner_and_lowest_non_winner[ocid][0];
-                    set_result!(item, Tenderer, id, R058, 0.0);
-                    set_tenderer_map!(item, ocid_tenderer_r058, ocid.clone(), id.clone());
+                    set_lot_result!(item, ocid, R058, *ratio, max);
+                    for id in &item.winner_and_lowest_non_winner[key][0] {
+                        set_result!(item, Tenderer, id, R058, 0.0);
+                        set_tenderer_map!(item, ocid_tenderer_r058, ocid.clone(), id.clone());
+                    }
                 }
             }
         }
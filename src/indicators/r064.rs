@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{is_status, set_meta, set_result, Calculate, Indicators, Settings};
+
+#[derive(Default)]
+pub struct R064 {
+    threshold: Option<usize>, // resolved in finalize()
+}
+
+impl Calculate for R064 {
+    fn new(settings: &mut Settings) -> Self {
+        Self {
+            threshold: std::mem::take(&mut settings.R064).unwrap_or_default().threshold,
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let (Some(Value::Array(awards)), Some(Value::Array(contracts))) =
+            (release.get("awards"), release.get("contracts"))
+        else {
+            return;
+        };
+
+        let mut award_dates = HashMap::new();
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(Value::String(id)) = award.get("id")
+                && let Some(Value::String(date)) = award.get("date")
+                && let Ok(date) = DateTime::parse_from_rfc3339(date)
+            {
+                award_dates.insert(id, date);
+            }
+        }
+
+        let mut longest_delay = None;
+
+        for contract in contracts {
+            if let Some(Value::String(award_id)) = contract.get("awardID")
+                && let Some(award_date) = award_dates.get(award_id)
+                && let Some(Value::String(date_signed)) = contract.get("dateSigned")
+                && let Ok(date_signed) = DateTime::parse_from_rfc3339(date_signed)
+                && let delay = (date_signed - *award_date).num_days()
+                // A contract signed before its award is a data quality issue.
+                && delay >= 0
+                && longest_delay.is_none_or(|other| delay > other)
+            {
+                longest_delay = Some(delay);
+            }
+        }
+
+        if let Some(delay) = longest_delay {
+            item.r064_delays.insert(ocid.to_owned(), delay as f64);
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        // If each OCID appears on one line of the file, no overwriting occurs.
+        item.r064_delays.extend(std::mem::take(&mut other.r064_delays));
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let (upper_fence, q3) = self.threshold.map_or_else(
+            || {
+                let mut data = Data::new(item.r064_delays.values().copied().collect::<Vec<_>>());
+                let q1 = data.lower_quartile();
+                let q3 = data.upper_quartile();
+                set_meta!(item, R064, "q1", q1);
+                set_meta!(item, R064, "q3", q3);
+                // q3 + IQR * 1.5
+                ((q3 - q1).mul_add(1.5, q3), q3)
+            },
+            |v| (v as f64, 1.0), // dummy value to pass guard
+        );
+
+        set_meta!(item, R064, "upper_fence", upper_fence);
+
+        // Skip if 75% of contracting processes are signed on the day of the award; otherwise, 75% are flagged.
+        if q3 > 0.0 {
+            for (ocid, delay) in std::mem::take(&mut item.r064_delays) {
+                if delay >= upper_fence {
+                    set_result!(item, OCID, ocid, R064, delay);
+                }
+            }
+        }
+    }
+}
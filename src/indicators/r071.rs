@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde_json::{Map, Value};
+use statrs::statistics::Data;
+use statrs::statistics::OrderStatistics;
+
+use crate::indicators::{
+    is_status, reduce_map, set_meta, set_result, set_tenderer_map, Calculate, Indicators, Settings, ValueIncrease,
+};
+
+#[derive(Default)]
+pub struct R071 {
+    threshold: Option<f64>, // resolved in finalize()
+    currency: Option<String>,
+}
+
+impl Calculate for R071 {
+    fn new(settings: &mut Settings) -> Self {
+        Self {
+            threshold: std::mem::take(&mut settings.R071).unwrap_or_default().threshold,
+            currency: settings.currency.clone(),
+        }
+    }
+
+    fn fold(&self, item: &mut Indicators, release: &Map<String, Value>, ocid: &str) {
+        let (Some(Value::Array(awards)), Some(Value::Array(contracts))) =
+            (release.get("awards"), release.get("contracts"))
+        else {
+            return;
+        };
+
+        // The total value of the contracts for each award, and whether any of the contracts is amended.
+        let mut contract_values: HashMap<&String, (f64, bool)> = HashMap::new();
+
+        for contract in contracts {
+            if let Some(Value::String(award_id)) = contract.get("awardID")
+                && let Some(value) = contract.get("value")
+                && let Some(amount) = item.get_amount(self.currency.as_ref(), value)
+            {
+                let entry = contract_values.entry(award_id).or_default();
+                entry.0 += amount;
+                entry.1 |=
+                    matches!(contract.get("amendments"), Some(Value::Array(amendments)) if !amendments.is_empty());
+            }
+        }
+
+        let mut award_total = 0.0;
+        let mut contract_total = 0.0;
+        let mut amended = false;
+        let mut supplier_ids = HashSet::new();
+
+        for award in awards {
+            if is_status!(award, "active")
+                && let Some(Value::String(id)) = award.get("id")
+                && let Some((contract_amount, contract_amended)) = contract_values.get(id)
+                && let Some(value) = award.get("value")
+                && let Some(award_amount) = item.get_amount(self.currency.as_ref(), value)
+            {
+                award_total += award_amount;
+                contract_total += contract_amount;
+                // The value grew through amendments.
+                amended |= *contract_amended && *contract_amount > award_amount;
+
+                if let Some(Value::Array(suppliers)) = award.get("suppliers") {
+                    for supplier in suppliers {
+                        if let Some(Value::String(id)) = supplier.get("id") {
+                            supplier_ids.insert(id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        // Avoid NaN errors.
+        if award_total > 0.0 {
+            let buyer_id = match release.get("buyer") {
+                Some(Value::Object(buyer)) => match buyer.get("id") {
+                    Some(Value::String(id)) => Some(id.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            item.r071_increases.insert(
+                ocid.to_owned(),
+                ValueIncrease {
+                    ratio: (contract_total - award_total) / award_total,
+                    amended,
+                    buyer_id,
+                    supplier_ids,
+                },
+            );
+        }
+    }
+
+    fn reduce(&self, item: &mut Indicators, other: &mut Indicators) {
+        // If each OCID appears on one line of the file, no overwriting occurs.
+        item.r071_increases.extend(std::mem::take(&mut other.r071_increases));
+
+        reduce_map!(item, other, ocid_buyer_r071);
+        reduce_map!(item, other, ocid_tenderer_r071);
+    }
+
+    fn finalize(&self, item: &mut Indicators) {
+        let increases = std::mem::take(&mut item.r071_increases);
+
+        let upper_fence = self.threshold.unwrap_or_else(|| {
+            // Most contract values equal award values. Use only increases, to not flag all increases.
+            let mut data = Data::new(
+                increases
+                    .values()
+                    .map(|increase| increase.ratio)
+                    .filter(|ratio| *ratio > 0.0)
+                    .collect::<Vec<_>>(),
+            );
+            let q1 = data.lower_quartile();
+            let q3 = data.upper_quartile();
+            set_meta!(item, R071, "q1", q1);
+            set_meta!(item, R071, "q3", q3);
+            // q3 + IQR * 1.5
+            (q3 - q1).mul_add(1.5, q3)
+        });
+
+        set_meta!(item, R071, "upper_fence", upper_fence);
+
+        let mut buyers: HashMap<String, usize> = HashMap::new();
+        let mut tenderers: HashMap<String, usize> = HashMap::new();
+        // Sort for consistent output.
+        let mut amended = BTreeMap::new();
+
+        for (ocid, increase) in increases {
+            // The value grew through amendments. This flag is reported separately from the fence.
+            if increase.ratio > 0.0 && increase.amended {
+                amended.insert(ocid.clone(), increase.ratio);
+            }
+
+            if increase.ratio > 0.0 && increase.ratio >= upper_fence {
+                set_result!(item, OCID, ocid, R071, increase.ratio);
+
+                if let Some(id) = increase.buyer_id {
+                    *buyers.entry(id.clone()).or_default() += 1;
+                    if item.map {
+                        item.maps.ocid_buyer_r071.insert(ocid.clone(), id);
+                    }
+                }
+                for id in increase.supplier_ids {
+                    *tenderers.entry(id.clone()).or_default() += 1;
+                    set_tenderer_map!(item, ocid_tenderer_r071, ocid.clone(), id);
+                }
+            }
+        }
+
+        for (ocid, ratio) in amended {
+            set_meta!(item, R071_amended, ocid, ratio);
+        }
+        for (id, count) in buyers {
+            set_result!(item, Buyer, id, R071, count as f64);
+        }
+        for (id, count) in tenderers {
+            set_result!(item, Tenderer, id, R071, count as f64);
+        }
+    }
+}
@@ -61,70 +61,77 @@ impl Calculate for SecondLowestBidRatio {
             return;
         }
 
-        let mut winner = None;
-        let mut winner_amount = None;
-        let mut lowest_non_winner = None;
-        let mut lowest_non_winner_amount = None;
+        let Some(competitions) = Indicators::get_competitions_if_all_awards_final(release) else {
+            return;
+        };
+
+        for competition in competitions {
+            let mut winner = None;
+            let mut winner_amount = None;
+            let mut lowest_non_winner = None;
+            let mut lowest_non_winner_amount = None;
 
-        if let Some((complete_awards, details)) =
-            Indicators::get_complete_awards_and_bids_if_all_awards_final(release)
             // If the only award is active, we assume all bids compete for all items. We assume any cancelled
             // or unsuccessful awards were previous attempts to award all items. If there are many active
             // awards, the dataset must describe lots, to know which bids compete with each other.
-            && complete_awards.len() == 1
-            && let Some(Value::Array(suppliers)) = complete_awards[0].get("suppliers")
-            // The tenderers on the bid must match the suppliers on the award. For now, we only support the
-            // simple case of a single supplier. https://github.com/open-contracting/cardinal-rs/issues/17
-            && suppliers.len() == 1
-            && let Some(Value::String(supplier_id)) = suppliers[0].get("id")
-        {
-            for (tenderer_id, amount, currency) in Indicators::get_tenderer_and_value_of_valid_bids(details) {
-                // Exclude missing currencies and different currencies than the selected currency. If no currency
-                // is selected (`self.currency`), use the first observed currency.
-                if currency
-                    == item
-                        .currency
-                        .get_or_insert_with(|| self.currency.as_ref().map_or_else(|| currency.clone(), Clone::clone))
+            if competition.awards.len() == 1
+                // The tenderers on the bid must match the suppliers on the award. A consortium has multiple IDs.
+                && let Some(supplier_ids) = Indicators::get_organization_ids(competition.awards[0], "suppliers")
+            {
+                for (tenderer_ids, amount, currency) in
+                    Indicators::get_tenderer_and_value_of_valid_bids(&competition.bids)
                 {
-                    if supplier_id == tenderer_id {
-                        // If the winner submitted multiple bids, take the lowest bid.
-                        if let Some(other) = winner_amount {
-                            if amount < other {
-                                winner = Some(tenderer_id);
+                    // Exclude missing currencies and different currencies than the selected currency. If no currency
+                    // is selected (`self.currency`), use the first observed currency.
+                    if currency
+                        == item.currency.get_or_insert_with(|| {
+                            self.currency.as_ref().map_or_else(|| currency.clone(), Clone::clone)
+                        })
+                    {
+                        if supplier_ids == tenderer_ids {
+                            // If the winner submitted multiple bids, take the lowest bid.
+                            if let Some(other) = winner_amount {
+                                if amount < other {
+                                    winner = Some(tenderer_ids);
+                                    winner_amount = Some(amount);
+                                }
+                            } else {
+                                winner = Some(tenderer_ids);
                                 winner_amount = Some(amount);
                             }
+                        } else if let Some(other) = lowest_non_winner_amount {
+                            if amount < other {
+                                lowest_non_winner = Some(tenderer_ids);
+                                lowest_non_winner_amount = Some(amount);
+                            }
                         } else {
-                            winner = Some(tenderer_id);
-                            winner_amount = Some(amount);
-                        }
-                    } else if let Some(other) = lowest_non_winner_amount {
-                        if amount < other {
-                            lowest_non_winner = Some(tenderer_id);
+                            lowest_non_winner = Some(tenderer_ids);
                             lowest_non_winner_amount = Some(amount);
                         }
                     } else {
-                        lowest_non_winner = Some(tenderer_id);
-                        lowest_non_winner_amount = Some(amount);
+                        warn!("{} is not {:?}, skipping.", currency, item.currency);
                     }
-                } else {
-                    warn!("{} is not {:?}, skipping.", currency, item.currency);
                 }
             }
-        }
 
-        if let Some(winner) = winner
-            && let Some(winner_amount) = winner_amount
-            && let Some(lowest_non_winner) = lowest_non_winner
-            && let Some(lowest_non_winner_amount) = lowest_non_winner_amount
-            // If the lowest bid didn't win, the award criteria aren't price only, as otherwise assumed.
-            && lowest_non_winner_amount >= winner_amount
-        {
-            item.second_lowest_bid_ratios.insert(
-                ocid.to_owned(),
-                (lowest_non_winner_amount - winner_amount) / winner_amount,
-            );
-            item.winner_and_lowest_non_winner
-                .insert(ocid.to_owned(), [winner.clone(), lowest_non_winner.clone()]);
+            if let Some(winner) = winner
+                && let Some(winner_amount) = winner_amount
+                && let Some(lowest_non_winner) = lowest_non_winner
+                && let Some(lowest_non_winner_amount) = lowest_non_winner_amount
+                // If the lowest bid didn't win, the award criteria aren't price only, as otherwise assumed.
+                && lowest_non_winner_amount >= winner_amount
+            {
+                let key = (ocid.to_owned(), competition.lot_id.clone());
+                item.second_lowest_bid_ratios
+                    .insert(key.clone(), (lowest_non_winner_amount - winner_amount) / winner_amount);
+                item.winner_and_lowest_non_winner.insert(
+                    key,
+                    [
+                        winner.into_iter().cloned().collect(),
+                        lowest_non_winner.into_iter().cloned().collect(),
+                    ],
+                );
+            }
         }
     }
 
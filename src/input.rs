@@ -0,0 +1,399 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use bzip2::bufread::MultiBzDecoder;
+use clap::ValueEnum;
+use flate2::bufread::MultiGzDecoder;
+use log::warn;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::value::RawValue;
+
+const GZIP: &[u8] = &[0x1f, 0x8b];
+const BZIP2: &[u8] = b"BZh";
+const ZSTD: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// The number of lines to read ahead of the consumer.
+const BOUND: usize = 1024;
+
+/// The format of the input data.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum InputFormat {
+    /// Line-delimited JSON, in which each line is a release
+    #[default]
+    Jsonl,
+    /// A JSON array, in which each element is a release
+    Array,
+    /// A release package, in which each entry of the `releases` array is a release
+    ReleasePackage,
+    /// A record package, in which the `compiledRelease` of each entry of the `records` array is a release
+    RecordPackage,
+}
+
+/// A source of lines of JSON text.
+pub trait Input {
+    /// Return an iterator over the lines of the input, with their locations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input can't be read.
+    fn numbered_lines<'a>(self) -> io::Result<NumberedLines<'a>>
+    where
+        Self: 'a;
+}
+
+pub type NumberedLines<'a> = Box<dyn Iterator<Item = (Location, io::Result<String>)> + Send + 'a>;
+
+/// The location of a line in the input.
+#[derive(Clone, Debug)]
+pub struct Location {
+    /// The file, if the input has multiple files.
+    pub file: Option<Arc<str>>,
+    /// The line number, starting at 1.
+    pub line: usize,
+}
+
+/// Input files, read one after the other.
+#[derive(Debug)]
+pub struct Files {
+    paths: Vec<PathBuf>,
+    format: InputFormat,
+}
+
+/// An error after which the rest of the input can't be read, like a truncated compressed stream or JSON array.
+#[derive(Debug)]
+pub struct InputError(io::Error);
+
+/// A reader that returns no more data after an error, because a decompressor can't recover from corrupt input.
+struct Fuse<R: Read> {
+    inner: R,
+    failed: bool,
+}
+
+/// A reader of the lines sent by the thread that parses the input.
+struct Lines {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+/// The elements of a JSON array, which are sent as lines.
+struct Elements<'a> {
+    sender: &'a SyncSender<io::Result<Vec<u8>>>,
+    record: bool,
+}
+
+/// The JSON object of a package, whose `key` member is a JSON array of elements.
+struct Package<'a> {
+    key: &'static str,
+    elements: Elements<'a>,
+}
+
+#[derive(Deserialize)]
+struct Record {
+    #[serde(rename = "compiledRelease")]
+    compiled_release: Option<Box<RawValue>>,
+}
+
+impl InputError {
+    /// Wrap the error, to distinguish it from an error in an individual line, like invalid UTF-8.
+    fn wrap(error: io::Error) -> io::Error {
+        io::Error::new(error.kind(), Self(error))
+    }
+
+    /// Return whether the error is an input error.
+    pub fn is(error: &io::Error) -> bool {
+        matches!(error.get_ref(), Some(inner) if inner.is::<Self>())
+    }
+}
+
+impl InputFormat {
+    /// Return a reader in which each line is a release. If the format isn't line-delimited JSON, the input is
+    /// parsed in another thread, one element at a time, in order to not load the whole input into memory.
+    ///
+    /// If an element isn't a release (like a record without a compiled release), its line is empty, so that line
+    /// numbers match element numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer can't be read.
+    pub fn reader(self, buffer: impl BufRead + Send + 'static) -> io::Result<Box<dyn BufRead + Send>> {
+        let key = match self {
+            Self::Jsonl => return Ok(Box::new(buffer)),
+            Self::Array => None,
+            Self::ReleasePackage => Some("releases"),
+            Self::RecordPackage => Some("records"),
+        };
+
+        let buffer = decompress(buffer)?;
+        let (sender, receiver) = mpsc::sync_channel(BOUND);
+
+        thread::spawn(move || {
+            let mut deserializer = serde_json::Deserializer::from_reader(buffer);
+            let elements = Elements {
+                sender: &sender,
+                record: matches!(self, Self::RecordPackage),
+            };
+            let result = match key {
+                Some(key) => Package { key, elements }.deserialize(&mut deserializer),
+                None => elements.deserialize(&mut deserializer),
+            };
+            if let Err(e) = result.and_then(|()| deserializer.end()) {
+                // If the receiver hung up, there's no one to report to.
+                let _ = sender.send(Err(InputError::wrap(io::Error::new(io::ErrorKind::InvalidData, e))));
+            }
+        });
+
+        Ok(Box::new(Lines {
+            receiver,
+            buffer: vec![],
+            position: 0,
+        }))
+    }
+}
+
+impl<B: BufRead + Send> Input for B {
+    fn numbered_lines<'a>(self) -> io::Result<NumberedLines<'a>>
+    where
+        Self: 'a,
+    {
+        let lines = decompress(self)?.lines().enumerate();
+
+        Ok(Box::new(lines.map(|(i, result)| {
+            (
+                Location {
+                    file: None,
+                    line: i + 1,
+                },
+                result,
+            )
+        })))
+    }
+}
+
+impl Files {
+    pub const fn new(paths: Vec<PathBuf>, format: InputFormat) -> Self {
+        Self { paths, format }
+    }
+}
+
+impl Input for Files {
+    fn numbered_lines<'a>(self) -> io::Result<NumberedLines<'a>>
+    where
+        Self: 'a,
+    {
+        let qualified = self.paths.len() > 1;
+        let format = self.format;
+
+        // Files are opened as they are read, in order to not exceed the limit on open files.
+        let lines = self.paths.into_iter().flat_map(move |path| -> NumberedLines<'static> {
+            let file: Arc<str> = path.display().to_string().into();
+            let buffer = File::open(&path)
+                .map(BufReader::new)
+                .and_then(|buffer| format.reader(buffer))
+                .and_then(decompress);
+
+            match buffer {
+                Ok(buffer) => Box::new(buffer.lines().enumerate().map(move |(i, result)| {
+                    let file = qualified.then(|| file.clone());
+                    (Location { file, line: i + 1 }, result)
+                })),
+                Err(e) => {
+                    let file = qualified.then_some(file);
+                    Box::new(std::iter::once((Location { file, line: 0 }, Err(InputError::wrap(e)))))
+                }
+            }
+        });
+
+        Ok(Box::new(lines))
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{file}:{}", self.line),
+            None => write!(f, "{}", self.line),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// The source is not set, because the message is the source's message.
+impl std::error::Error for InputError {}
+
+impl Serialize for Location {
+    // Serialize as a number, if possible, for compatibility with the single-file CSV output.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.file {
+            Some(_) => serializer.collect_str(self),
+            None => serializer.serialize_u64(self.line as u64),
+        }
+    }
+}
+
+impl<R: Read> Read for Fuse<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Ok(0);
+        }
+        self.inner.read(buf).map_err(|e| {
+            self.failed = true;
+            InputError::wrap(e)
+        })
+    }
+}
+
+impl Read for Lines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl BufRead for Lines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position >= self.buffer.len() {
+            match self.receiver.recv() {
+                Ok(Ok(line)) => {
+                    self.buffer = line;
+                    self.position = 0;
+                }
+                Ok(Err(e)) => return Err(e),
+                // The sender hung up: that is, the input is exhausted.
+                Err(_) => return Ok(&[]),
+            }
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
+
+impl Elements<'_> {
+    fn send<E: de::Error>(&self, value: Option<&RawValue>) -> Result<(), E> {
+        // Newlines in JSON text can only be insignificant whitespace, as newlines in strings must be escaped.
+        let mut line = value.map_or_else(Vec::new, |value| {
+            value
+                .get()
+                .bytes()
+                .map(|byte| if byte == b'\n' || byte == b'\r' { b' ' } else { byte })
+                .collect()
+        });
+        line.push(b'\n');
+
+        self.sender
+            .send(Ok(line))
+            .map_err(|_| E::custom("the reader is closed"))
+    }
+}
+
+impl<'de> Visitor<'de> for Elements<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut i = 0;
+        if self.record {
+            while let Some(record) = seq.next_element::<Record>()? {
+                i += 1;
+                if record.compiled_release.is_none() {
+                    warn!("Record {i} has no compiledRelease, skipping.");
+                }
+                self.send(record.compiled_release.as_deref())?;
+            }
+        } else {
+            while let Some(value) = seq.next_element::<Box<RawValue>>()? {
+                self.send(Some(&value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Elements<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Package<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a JSON object with a `{}` array", self.key)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut elements = Some(self.elements);
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.key
+                && let Some(elements) = elements.take()
+            {
+                map.next_value_seed(elements)?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        if elements.is_some() {
+            return Err(de::Error::missing_field(self.key));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Package<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+fn fuse<'a>(inner: impl Read + Send + 'a) -> Box<dyn BufRead + Send + 'a> {
+    Box::new(BufReader::new(Fuse { inner, failed: false }))
+}
+
+/// Return a reader that decompresses the buffer, if the buffer starts with the magic number of gzip, bzip2 or
+/// Zstandard. Otherwise, return the buffer as-is.
+///
+/// # Errors
+///
+/// Returns an error if the buffer can't be read.
+pub fn decompress<'a>(mut buffer: impl BufRead + Send + 'a) -> io::Result<Box<dyn BufRead + Send + 'a>> {
+    // A pipe can return fewer bytes than the longest magic number. Read until the magic number or the end.
+    let mut magic = Vec::with_capacity(ZSTD.len());
+    (&mut buffer).take(ZSTD.len() as u64).read_to_end(&mut magic)?;
+    let buffer = io::Cursor::new(magic).chain(buffer);
+    let magic = buffer.get_ref().0.get_ref();
+
+    Ok(if magic.starts_with(GZIP) {
+        fuse(MultiGzDecoder::new(buffer))
+    } else if magic.starts_with(BZIP2) {
+        fuse(MultiBzDecoder::new(buffer))
+    } else if magic.starts_with(ZSTD) {
+        fuse(zstd::Decoder::with_buffer(buffer)?)
+    } else {
+        Box::new(buffer)
+    })
+}
@@ -1,36 +1,60 @@
 #![feature(let_chains)]
 
 pub mod indicators;
+mod input;
 mod queue;
+mod rules;
 pub mod standard;
 
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{self, BufRead, BufWriter, Write};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc};
+use clap::ValueEnum;
 use indexmap::IndexMap;
+use itertools::Itertools;
 use log::warn;
 use rayon::prelude::*;
+use serde::Serialize;
 use serde_json::{Map, Value};
 
+use crate::indicators::exchange_rates::ExchangeRates;
 use crate::indicators::is_status;
 use crate::indicators::r003::R003;
+use crate::indicators::r006::R006;
+use crate::indicators::r011::R011;
 use crate::indicators::r018::R018;
+use crate::indicators::r020::R020;
 use crate::indicators::r024::R024;
 use crate::indicators::r025::R025;
 use crate::indicators::r028::R028;
+use crate::indicators::r029::R029;
 use crate::indicators::r030::R030;
+use crate::indicators::r031::R031;
 use crate::indicators::r035::R035;
 use crate::indicators::r036::R036;
 use crate::indicators::r038::R038;
+use crate::indicators::r043::R043;
+use crate::indicators::r044::R044;
 use crate::indicators::r048::R048;
+use crate::indicators::r049::R049;
+use crate::indicators::r050::R050;
+use crate::indicators::r055::R055;
 use crate::indicators::r058::R058;
+use crate::indicators::r064::R064;
+use crate::indicators::r071::R071;
 use crate::indicators::util::{SecondLowestBidRatio, Tenderers};
 pub use crate::indicators::{Calculate, Codelist, Exclusions, Group, Indicator, Indicators, Modifications, Settings};
+use crate::indicators::{Competition, Maps, Row};
+pub use crate::input::{decompress, Files, Input, InputError, InputFormat, Location, NumberedLines};
 use crate::queue::Job;
-use crate::standard::{AWARD_STATUS, BID_STATUS};
+use crate::rules::Compiled;
+use crate::standard::{
+    AWARD_STATUS, BID_STATUS, CONTRACT_STATUS, PARTY_ROLE, PROCUREMENT_METHOD, TENDER_STATUS, WHOLE_LIST_MERGE,
+};
 
 macro_rules! add_indicators {
     ( $indicators:ident , $settings:ident , $( $indicator:ident ) ,* , ) => {
@@ -61,9 +85,11 @@ fn parse_pipe_separated_value_with_default(value: Option<String>, default: Strin
 ///
 /// # Errors
 ///
+#[allow(clippy::too_many_lines)]
 pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
     let content = b"\
 ; currency = USD
+; exchange_rates = rates.csv
 ; no_price_comparison_procurement_methods = Random Selection
 ; price_comparison_procurement_methods = Reverse Auction
 
@@ -78,6 +104,7 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 ; bid_status = valid
 ; award_status = active
 ; party_roles = true
+; timezone = -05:00
 
 [redactions]
 ; amount = 0
@@ -91,6 +118,7 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 ; prefix_buyer_or_procuring_entity_id = DO-UC-
 ; prefix_tenderer_or_supplier_id = DO-RPE-
 ; split_procurement_method_details = -
+; date_formats = %d/%m/%Y
 
 [codelists.bid_status]
 ; qualified = valid
@@ -98,6 +126,22 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 [codelists.award_status]
 ; Active = active
 
+[codelists.procurement_method]
+; Licitacion Publica = open
+
+[codelists.tender_status]
+; Adjudicado = complete
+
+[codelists.contract_status]
+; Vigente = active
+
+[codelists.party_role]
+; Comprador = buyer
+
+; [rules.estimated_value]
+; path = /tender/estimatedValue
+; rename = value
+
 ; `indicators` command
 ;
 ; Read the documentation at:
@@ -114,9 +158,25 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 ; emergency = 10
 ; international = 25
 
+[R006]
+; threshold = 100000
+; procurement_methods = limited
+
+[R006.procurement_method_details]
+; direct = 50000
+
+[R011]
+; procurement_threshold = 100000
+; margin = 0.1
+; window = 30
+; digits = 2
+; threshold = 3
+
 [R018]
 ; procurement_methods = open|selective
 
+[R020]
+
 [R024]
 ; threshold = 0.05
 
@@ -126,8 +186,19 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 
 [R028]
 
+[R029]
+; threshold = 5
+; minimum_contracting_processes = 2
+
 [R030]
 
+[R031]
+; procurement_methods = open|selective
+
+[R031.procurement_method_details]
+; emergency = 1|30
+; international = 5|60
+
 [R035]
 ; threshold = 1
 
@@ -138,13 +209,39 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
 ; minimum_submitted_bids = 2
 ; minimum_contracting_processes = 2
 
+[R043]
+; threshold = 365
+; registration_dates = registration_dates.csv
+
+[R044]
+
 [R048]
 ; digits = 2
 ; threshold = 10
 ; minimum_contracting_processes = 20
 
+[R049]
+; threshold = 0.8
+; minimum_contracting_processes = 20
+
+[R050]
+; procurement_methods = limited
+; procurement_method_details = Direct Award|Emergency
+; window = 365
+; threshold = 5
+; minimum_contracting_processes = 2
+
+[R055]
+; threshold = 0.05
+
 [R058]
 ; threshold = 0.5
+
+[R064]
+; threshold = 60
+
+[R071]
+; threshold = 0.2
 ";
 
     let stdout = path == &PathBuf::from("-");
@@ -161,46 +258,108 @@ pub fn init(path: &PathBuf, force: &bool) -> std::io::Result<bool> {
     Ok(stdout)
 }
 
+///
+/// # Errors
+///
+pub fn results_to_csv(item: &Indicators, path: &PathBuf) -> Result<usize> {
+    let created_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let mut rows = item.rows(&created_at);
+
+    if path == &PathBuf::from("-") {
+        let mut writer = csv::Writer::from_writer(io::stdout().lock());
+        for row in &rows {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+        return Ok(rows.len());
+    }
+
+    let exists = path.exists();
+
+    // Skip rows that were written by a previous run, so that results can be appended to the same file. Compare the
+    // raw columns, in case the file has indicator codes or results that this version doesn't read.
+    if exists {
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.clone();
+        let mut positions = [0; Row::KEY.len()];
+        for (position, name) in positions.iter_mut().zip(Row::KEY) {
+            let Some(index) = headers.iter().position(|header| header == name) else {
+                bail!("{}: {name} column must be set", path.display());
+            };
+            *position = index;
+        }
+
+        let mut seen = HashSet::new();
+        for result in reader.records() {
+            let record = result?;
+            seen.insert(positions.map(|index| record.get(index).unwrap_or_default().to_owned()));
+        }
+        rows.retain(|row| !seen.contains(&row.key()));
+    }
+
+    let file = OpenOptions::new().append(true).create(true).open(path)?;
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(file);
+    if !exists {
+        writer.write_record(Row::HEADER)?;
+    }
+    for row in &rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+
+    Ok(rows.len())
+}
+
+// An input error means that the rest of the input is unread. Fail, rather than return partial results.
+fn input_error(location: &Location, e: io::Error) -> anyhow::Error {
+    match &location.file {
+        Some(file) => anyhow::anyhow!("{file}: {e}"),
+        None => e.into(),
+    }
+}
+
 fn fold_reduce<T: Send, Fold, Reduce, Finalize>(
-    buffer: impl BufRead + Send,
+    input: impl Input,
     default: fn() -> T,
     fold: Fold,
     reduce: Reduce,
     finalize: Finalize,
 ) -> Result<T>
 where
-    Fold: Fn(T, Value) -> T + Sync,
+    Fold: Fn(T, Value, usize) -> T + Sync,
     Reduce: Fn(T, T) -> T + Send + Sync,
     Finalize: Fn(T) -> Result<T> + Sync,
 {
-    let item = buffer
-        .lines()
+    let item = input
+        .numbered_lines()?
+        // The position of the line in the input, across files, because par_bridge doesn't preserve order.
         .enumerate()
-        // Other iterators might be faster. par_bridge preserves the index for debugging.
+        // Other iterators might be faster. par_bridge preserves the location for debugging.
         .par_bridge()
-        .fold(default, |mut item, (i, lines_result)| {
+        .try_fold(default, |mut item, (position, (location, lines_result))| -> Result<T> {
             match lines_result {
                 Ok(string) => {
                     match serde_json::from_str(&string) {
                         Ok(value) => {
-                            item = fold(item, value);
+                            item = fold(item, value, position);
                         }
                         Err(e) => {
                             // Skip empty lines silently.
                             // https://stackoverflow.com/a/64361042/244258
                             if !string.as_bytes().iter().all(u8::is_ascii_whitespace) {
-                                warn!("Line {} is invalid JSON, skipping. [{e}]", i + 1);
+                                warn!("Line {location} is invalid JSON, skipping. [{e}]");
                             }
                         }
                     }
                 }
+                Err(e) if InputError::is(&e) => return Err(input_error(&location, e)),
                 // Err: https://doc.rust-lang.org/std/io/enum.ErrorKind.html
                 // https://github.com/rust-lang/rust/blob/1.65.0/library/std/src/io/buffered/bufreader.rs#L362-L365
-                Err(e) => warn!("Line {} caused an I/O error, skipping. [{e}]", i + 1),
+                Err(e) => warn!("Line {location} caused an I/O error, skipping. [{e}]"),
             }
-            item
+            Ok(item)
         })
-        .reduce(default, reduce);
+        .try_reduce(default, |item, other| Ok(reduce(item, other)))?;
 
     finalize(item)
 }
@@ -217,11 +376,78 @@ impl Indicators {
         }
     }
 
+    /// Read the JSON output of the `indicators` command. The "Meta" key is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the "Maps" key is missing, because organizations' results can't be mapped to contracting
+    /// processes without it.
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        let mut object: Map<String, Value> = serde_json::from_reader(reader)?;
+        object.remove("Meta");
+        let Some(maps) = object.remove("Maps") else {
+            bail!("Maps: key must be set, with the --map option of the indicators command");
+        };
+
+        Ok(Self {
+            results: serde_json::from_value(Value::Object(object))?,
+            maps: serde_json::from_value(maps)?,
+            ..Default::default()
+        })
+    }
+
+    /// Return one row per result and contracting process. For organizations (buyers, procuring entities and
+    /// tenderers), the contracting processes are found using the `maps` field.
+    pub fn rows(&self, created_at: &str) -> Vec<Row> {
+        let mut rows = vec![];
+        let mut lookups = HashMap::new();
+
+        for group in [Group::OCID, Group::Buyer, Group::ProcuringEntity, Group::Tenderer] {
+            let Some(subresults) = self.results.get(&group) else {
+                continue;
+            };
+
+            for (id, results) in subresults {
+                let mut results = results.iter().collect::<Vec<_>>();
+                results.sort_unstable_by_key(|(code, _)| *code);
+
+                let column = |subject: Group| if group == subject { id.clone() } else { String::new() };
+
+                for (code, result) in results {
+                    let row = |ocid: &str| Row {
+                        ocid: ocid.to_owned(),
+                        subject: group.clone(),
+                        code: code.clone(),
+                        result: *result,
+                        buyer_id: column(Group::Buyer),
+                        procuring_entity_id: column(Group::ProcuringEntity),
+                        tenderer_id: column(Group::Tenderer),
+                        created_at: created_at.to_owned(),
+                    };
+
+                    if group == Group::OCID {
+                        rows.push(row(id));
+                    } else {
+                        let lookup = lookups
+                            .entry((group.clone(), code.clone()))
+                            .or_insert_with(|| self.maps.ocids(&group, code));
+                        if let Some(ocids) = lookup.get(id.as_str()) {
+                            rows.extend(ocids.iter().map(|ocid| row(ocid)));
+                        }
+                    }
+                }
+            }
+        }
+
+        rows
+    }
+
     ///
     /// # Errors
     ///
     #[rustfmt::skip]
-    pub fn run(buffer: impl BufRead + Send, mut settings: Settings, map: &bool) -> Result<Self> {
+    #[allow(clippy::too_many_lines)]
+    pub fn run(input: impl Input, mut settings: Settings, map: &bool) -> Result<Self> {
         let empty_set: HashSet<String> = HashSet::new();
         let mut indicators: Vec<Box<dyn Calculate + Sync>> = vec![];
 
@@ -229,6 +455,12 @@ impl Indicators {
         let exclusions = std::mem::take(&mut settings.exclusions).unwrap_or_default();
         let exclude_procurement_method_details = parse_pipe_separated_value(exclusions.procurement_method_details);
 
+        let exchange_rates = match (std::mem::take(&mut settings.exchange_rates), &settings.currency) {
+            (Some(path), Some(currency)) => Some(ExchangeRates::from_path(&path, currency.clone())?),
+            (Some(_), None) => bail!("exchange_rates: currency must be set, to convert to the main currency"),
+            (None, _) => None,
+        };
+
         // is_some() must run before indicator initialization, which mutates settings.
         if *map && (settings.R025.is_some() || settings.R038.is_some() || settings.R048.is_some()) {
             indicators.push(Box::new(Tenderers::new(&mut settings)));
@@ -241,20 +473,36 @@ impl Indicators {
             warn!("no_price_comparison_procurement_methods has no effect if price_comparison_procurement_methods is set.");
         }
 
+        // R043 can read a CSV file, which can fail.
+        if settings.R043.is_some() {
+            indicators.push(Box::new(R043::try_new(&mut settings)?));
+        }
+
         add_indicators!(
             indicators,
             settings,
             R003,
+            R006,
+            R011,
             R018,
+            R020,
             R024,
             R025,
             R028,
+            R029,
             R030,
+            R031,
             R035,
             R036,
             R038,
+            R044,
             R048,
+            R049,
+            R050,
+            R055,
             R058,
+            R064,
+            R071,
         );
 
         let identity = if *map {
@@ -264,16 +512,22 @@ impl Indicators {
         };
 
         fold_reduce(
-            buffer,
+            input,
             identity,
-            |mut item, value| {
-                if let Value::Object(release) = value
-                    && let Some(Value::String(ocid)) = release.get("ocid")
+            |mut item, value, _| {
+                if let Value::Object(mut release) = value
+                    && release.get("ocid").is_some_and(Value::is_string)
                     && !Self::is_cancelled_contracting_process(&release)
                     && Self::matches_procurement_method_details(&release, &empty_set, &exclude_procurement_method_details)
                 {
-                    for indicator in &indicators {
-                        indicator.fold(&mut item, &release, ocid);
+                    if let Some(exchange_rates) = &exchange_rates {
+                        exchange_rates.convert(&mut item, &mut release);
+                    }
+
+                    if let Some(Value::String(ocid)) = release.get("ocid") {
+                        for indicator in &indicators {
+                            indicator.fold(&mut item, &release, ocid);
+                        }
                     }
                 }
 
@@ -292,6 +546,9 @@ impl Indicators {
 
                 // Note: Buyer and ProcuringEntity indicators are only calculated in finalize().
 
+                item.converted_values += other.converted_values;
+                item.skipped_values += other.skipped_values;
+
                 for indicator in &indicators {
                     indicator.reduce(&mut item, &mut other);
                 }
@@ -303,6 +560,10 @@ impl Indicators {
                     indicator.finalize(&mut item);
                 }
 
+                if exchange_rates.is_some() {
+                    ExchangeRates::finalize(&mut item);
+                }
+
                 // These keys are always set by the reduce closure.
                 if item.results[&Group::OCID].is_empty() {
                     item.results.swap_remove(&Group::OCID);
@@ -326,10 +587,18 @@ impl Indicators {
     fn get_complete_awards_and_bids_if_all_awards_final(
         release: &Map<String, Value>,
     ) -> Option<(Vec<&Value>, &Vec<Value>)> {
-        if let Some(Value::Array(awards)) = release.get("awards")
-            && let Some(Value::Object(bids)) = release.get("bids")
+        if let Some(Value::Object(bids)) = release.get("bids")
             && let Some(Value::Array(details)) = bids.get("details")
+            && let Some(complete_awards) = Self::get_complete_awards_if_all_awards_final(release)
         {
+            return Some((complete_awards, details));
+        }
+
+        None
+    }
+
+    fn get_complete_awards_if_all_awards_final(release: &Map<String, Value>) -> Option<Vec<&Value>> {
+        if let Some(Value::Array(awards)) = release.get("awards") {
             let mut complete_awards = vec![];
 
             // An award must be in a final state, in order for indicator results to be stable.
@@ -345,12 +614,72 @@ impl Indicators {
                 }
             }
 
-            return Some((complete_awards, details));
+            return Some(complete_awards);
         }
 
         None
     }
 
+    // Bids are returned even if there are no awards, because "all" awards are final.
+    fn get_competitions_if_all_awards_final(release: &Map<String, Value>) -> Option<Vec<Competition<'_>>> {
+        Self::get_complete_awards_and_bids_if_all_awards_final(release)
+            .map(|(complete_awards, details)| Self::split_by_lot(release, complete_awards, details.iter().collect()))
+    }
+
+    // If the contracting process has lots, and its awards or bids relate to lots, each lot is a competition, with the
+    // awards and bids that relate to it. Otherwise, the contracting process is one competition.
+    fn split_by_lot<'a>(
+        release: &'a Map<String, Value>,
+        awards: Vec<&'a Value>,
+        bids: Vec<&'a Value>,
+    ) -> Vec<Competition<'a>> {
+        if let Some(Value::Object(tender)) = release.get("tender")
+            && let Some(Value::Array(lots)) = tender.get("lots")
+            && !lots.is_empty()
+            // Many publishers set tender/lots, without setting relatedLots on awards and bids.
+            && awards.iter().chain(&bids).any(|object| {
+                matches!(object.get("relatedLots"), Some(Value::Array(related_lots)) if !related_lots.is_empty())
+            })
+        {
+            lots.iter()
+                .filter_map(|lot| lot.get("id").and_then(Self::get_id))
+                .map(|lot_id| Competition {
+                    awards: awards
+                        .iter()
+                        .copied()
+                        .filter(|award| Self::is_related_to_lot(award, &lot_id))
+                        .collect(),
+                    bids: bids
+                        .iter()
+                        .copied()
+                        .filter(|bid| Self::is_related_to_lot(bid, &lot_id))
+                        .collect(),
+                    lot_id: Some(lot_id),
+                })
+                .collect()
+        } else {
+            vec![Competition {
+                lot_id: None,
+                awards,
+                bids,
+            }]
+        }
+    }
+
+    // Lot IDs can be strings or integers.
+    fn get_id(id: &Value) -> Option<String> {
+        match id {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn is_related_to_lot(object: &Value, lot_id: &str) -> bool {
+        matches!(object.get("relatedLots"), Some(Value::Array(related_lots))
+            if related_lots.iter().any(|id| Self::get_id(id).is_some_and(|id| id == lot_id)))
+    }
+
     fn is_cancelled_contracting_process(release: &Map<String, Value>) -> bool {
         if let Some(Value::Object(tender)) = release.get("tender")
             && let Some(Value::String(status)) = tender.get("status")
@@ -395,6 +724,25 @@ impl Indicators {
         }
     }
 
+    // Returns the amount, if it is in the main currency. If no currency is selected, use the first observed currency.
+    fn get_amount(&mut self, currency: Option<&String>, value: &Value) -> Option<f64> {
+        if let Some(Value::Number(amount)) = value.get("amount")
+            && let Some(Value::String(other)) = value.get("currency")
+            && let Some(amount) = amount.as_f64()
+        {
+            if other
+                == self
+                    .currency
+                    .get_or_insert_with(|| currency.map_or_else(|| other.clone(), Clone::clone))
+            {
+                return Some(amount);
+            }
+            warn!("{} is not {:?}, skipping.", other, self.currency);
+        }
+
+        None
+    }
+
     // Includes pending, valid and disqualified bids.
     fn get_submitted_bids(release: &Map<String, Value>) -> Vec<&Value> {
         let mut submitted_bids = vec![];
@@ -415,7 +763,25 @@ impl Indicators {
         submitted_bids
     }
 
-    fn get_tenderer_and_value_of_valid_bids(details: &Vec<Value>) -> Vec<(&String, f64, &String)> {
+    // Returns the IDs of the organizations (like "tenderers" or "suppliers"), if each organization has an ID.
+    // A consortium has multiple IDs.
+    fn get_organization_ids<'a>(object: &'a Value, field: &str) -> Option<BTreeSet<&'a String>> {
+        if let Some(Value::Array(organizations)) = object.get(field)
+            && !organizations.is_empty()
+        {
+            organizations
+                .iter()
+                .map(|organization| match organization.get("id") {
+                    Some(Value::String(id)) => Some(id),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            None
+        }
+    }
+
+    fn get_tenderer_and_value_of_valid_bids<'a>(details: &[&'a Value]) -> Vec<(BTreeSet<&'a String>, f64, &'a String)> {
         let mut tuples = vec![];
 
         for bid in details {
@@ -423,13 +789,11 @@ impl Indicators {
                 && let Some(Value::Object(value)) = bid.get("value")
                 && let Some(Value::Number(amount)) = value.get("amount")
                 && let Some(Value::String(currency)) = value.get("currency")
-                && let Some(Value::Array(tenderers)) = bid.get("tenderers")
-                && tenderers.len() == 1
-                && let Some(Value::String(tenderer_id)) = tenderers[0].get("id")
+                && let Some(tenderer_ids) = Self::get_organization_ids(bid, "tenderers")
                 && let Some(amount) = amount.as_f64()
                 && status == "valid"
             {
-                tuples.push((tenderer_id, amount, currency));
+                tuples.push((tenderer_ids, amount, currency));
             }
         }
 
@@ -437,6 +801,89 @@ impl Indicators {
     }
 }
 
+impl Maps {
+    /// Return the contracting processes for each organization, using the map for the group's results for the
+    /// indicator. R025, R038 and R048 use all contracting processes in which the tenderer submitted bids.
+    fn ocids(&self, group: &Group, indicator: &Indicator) -> HashMap<&str, Vec<&str>> {
+        let mut lookup: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        match group {
+            Group::Buyer | Group::ProcuringEntity => {
+                let map = match (group, indicator) {
+                    (Group::Buyer, Indicator::R006) => &self.ocid_buyer_r006,
+                    (Group::Buyer, Indicator::R011) => &self.ocid_buyer_r011,
+                    (Group::Buyer, Indicator::R038) => &self.ocid_buyer_r038,
+                    (Group::ProcuringEntity, Indicator::R038) => &self.ocid_procuringentity_r038,
+                    (Group::Buyer, Indicator::R049) => &self.ocid_buyer_r049,
+                    (Group::Buyer, Indicator::R050) => &self.ocid_buyer_r050,
+                    (Group::Buyer, Indicator::R071) => &self.ocid_buyer_r071,
+                    _ => return lookup,
+                };
+                for (ocid, id) in map {
+                    lookup.entry(id).or_default().push(ocid);
+                }
+            }
+            Group::Tenderer => {
+                let map = match indicator {
+                    Indicator::R006 => &self.ocid_tenderer_r006,
+                    Indicator::R024 => &self.ocid_tenderer_r024,
+                    Indicator::R028 => &self.ocid_tenderer_r028,
+                    Indicator::R029 => &self.ocid_tenderer_r029,
+                    Indicator::R030 => &self.ocid_tenderer_r030,
+                    Indicator::R035 => &self.ocid_tenderer_r035,
+                    Indicator::R043 => &self.ocid_tenderer_r043,
+                    Indicator::R044 => &self.ocid_tenderer_r044,
+                    Indicator::R049 => &self.ocid_tenderer_r049,
+                    Indicator::R050 => &self.ocid_tenderer_r050,
+                    Indicator::R058 => &self.ocid_tenderer_r058,
+                    Indicator::R071 => &self.ocid_tenderer_r071,
+                    _ => &self.ocid_tenderer,
+                };
+                for (ocid, ids) in map {
+                    for id in ids {
+                        lookup.entry(id).or_default().push(ocid);
+                    }
+                }
+            }
+            Group::OCID => {}
+        }
+
+        // Maps are unordered. Sort for consistent output.
+        for ocids in lookup.values_mut() {
+            ocids.sort_unstable();
+        }
+
+        lookup
+    }
+}
+
+impl Row {
+    const HEADER: [&'static str; 8] = [
+        "ocid",
+        "subject",
+        "code",
+        "result",
+        "buyer_id",
+        "procuring_entity_id",
+        "tenderer_id",
+        "created_at",
+    ];
+
+    // The columns that identify a row.
+    const KEY: [&'static str; 5] = ["ocid", "code", "buyer_id", "procuring_entity_id", "tenderer_id"];
+
+    fn key(&self) -> [String; 5] {
+        [
+            self.ocid.clone(),
+            // Like the serialized value.
+            format!("{:?}", self.code),
+            self.buyer_id.clone(),
+            self.procuring_entity_id.clone(),
+            self.tenderer_id.clone(),
+        ]
+    }
+}
+
 macro_rules! stringify {
     ( $object:ident , $key:expr ) => {
         if let Some(Value::Number(id)) = $object.get($key) {
@@ -497,6 +944,320 @@ macro_rules! prepare_id_array {
     };
 }
 
+/// The format in which to write quality issues.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ErrorsFormat {
+    /// CSV rows, with the columns: line, ocid, path, array indexes, incorrect value, error description
+    #[default]
+    Csv,
+    /// Line-delimited JSON, in which each line is an issue, including issues that were fixed
+    Jsonl,
+}
+
+/// The type of quality issue.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCode {
+    NotSet,
+    Invalid,
+    NoTimezone,
+    IsZero,
+    Renamed,
+    Deleted,
+}
+
+/// A quality issue in a compiled release.
+#[derive(Debug)]
+struct Issue {
+    /// The path to the field, in which `[]` is an array.
+    path: &'static str,
+    /// The indexes of the arrays in the path.
+    indexes: Vec<usize>,
+    code: IssueCode,
+    /// The original value.
+    value: Value,
+    /// The value that replaced the original value, if the issue was fixed.
+    fix: Option<Value>,
+    /// Whether the fix depends on the original value, like a date in another format.
+    normalized: bool,
+}
+
+/// A quality issue, as a line of the JSON output.
+#[derive(Serialize)]
+struct IssueLine<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    line: usize,
+    ocid: &'a Value,
+    pointer: String,
+    path: &'static str,
+    code: IssueCode,
+    value: &'a Value,
+    fix: Option<&'a Value>,
+}
+
+impl IssueCode {
+    const fn description(self) -> &'static str {
+        match self {
+            Self::NotSet => "not set",
+            Self::Invalid => "invalid",
+            Self::NoTimezone => "no timezone",
+            Self::IsZero => "is zero",
+            Self::Renamed => "renamed",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+impl Issue {
+    const fn new(path: &'static str, indexes: Vec<usize>, code: IssueCode, value: Value) -> Self {
+        Self {
+            path,
+            indexes,
+            code,
+            value,
+            fix: None,
+            normalized: false,
+        }
+    }
+
+    fn fixed(mut self, fix: Value) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    fn normalized(mut self, fix: Value) -> Self {
+        self.fix = Some(fix);
+        self.normalized = true;
+        self
+    }
+
+    // Replace each `[]` in the path with its index, like "/awards/0/status".
+    fn pointer(&self) -> String {
+        let mut pointer = String::new();
+        for (i, part) in self.path.split("[]").enumerate() {
+            if i > 0 {
+                pointer.push('/');
+                pointer.push_str(&self.indexes[i - 1].to_string());
+            }
+            pointer.push_str(part);
+        }
+        pointer
+    }
+}
+
+fn write_issues<W: Write>(
+    writer: W,
+    issues: &[Issue],
+    location: &Location,
+    ocid: &Value,
+    format: ErrorsFormat,
+) -> Result<()> {
+    match format {
+        // Fixed issues are not reported in CSV, for backwards compatibility.
+        ErrorsFormat::Csv => {
+            let mut rows = csv::Writer::from_writer(writer);
+            for issue in issues.iter().filter(|issue| issue.fix.is_none()) {
+                rows.serialize((
+                    location,
+                    ocid,
+                    issue.path,
+                    issue.indexes.iter().join("."),
+                    issue.value.as_str().unwrap_or_default(),
+                    issue.code.description(),
+                ))?;
+            }
+        }
+        ErrorsFormat::Jsonl => {
+            let mut writer = writer;
+            for issue in issues {
+                let line = IssueLine {
+                    file: location.file.as_deref(),
+                    line: location.line,
+                    ocid,
+                    pointer: issue.pointer(),
+                    path: issue.path,
+                    code: issue.code,
+                    value: &issue.value,
+                    fix: issue.fix.as_ref(),
+                };
+                serde_json::to_writer(&mut writer, &line)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Counts of quality issues, returned by the prepare command.
+#[derive(Debug, Default)]
+pub struct Summary {
+    /// The number of times each field was checked.
+    checked: HashMap<&'static str, usize>,
+    /// The number of issues, by path, code and fix.
+    issues: HashMap<(&'static str, IssueCode, Option<String>), usize>,
+}
+
+impl Summary {
+    fn check(&mut self, path: &'static str) {
+        *self.checked.entry(path).or_default() += 1;
+    }
+
+    fn add(&mut self, issues: &[Issue]) {
+        for issue in issues {
+            // Strings are written without quotes, like in the CSV output of the quality issues.
+            // Normalized values are counted together, as each fix is different.
+            let fix = issue.fix.as_ref().map(|fix| {
+                if issue.normalized {
+                    "(normalized)".into()
+                } else {
+                    fix.as_str().map_or_else(|| fix.to_string(), String::from)
+                }
+            });
+            *self.issues.entry((issue.path, issue.code, fix)).or_default() += 1;
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        for (k, v) in other.checked {
+            *self.checked.entry(k).or_default() += v;
+        }
+        for (k, v) in other.issues {
+            *self.issues.entry(k).or_default() += v;
+        }
+        self
+    }
+
+    /// Write the summary as CSV rows, with a header row. Each row is a path, issue and fix, with the number of
+    /// issues, the number of times the field was checked, and the percentage of checks that found the issue.
+    ///
+    /// # Errors
+    ///
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["path", "issue", "fix", "count", "checked", "percentage"])?;
+
+        let mut rows: Vec<_> = self.issues.iter().collect();
+        rows.sort_unstable_by_key(|(key, _)| *key);
+
+        for ((path, code, fix), count) in rows {
+            let checked = self.checked.get(path).copied().unwrap_or_default();
+            let percentage = *count as f64 / checked as f64 * 100.0;
+            writer.serialize((
+                path,
+                code.description(),
+                fix,
+                count,
+                checked,
+                format!("{percentage:.1}"),
+            ))?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+// Replace the code using the mapping, and report the code if it's invalid.
+fn remap(
+    code: &mut String,
+    mapping: &HashMap<String, String>,
+    codes: &HashSet<&str>,
+    issue: impl Fn(Value) -> Issue,
+    issues: &mut Vec<Issue>,
+) {
+    if let Some(replacement) = mapping.get(code) {
+        if !codes.contains(code.as_str()) && codes.contains(replacement.as_str()) {
+            issues.push(issue(Value::String(code.clone())).fixed(Value::String(replacement.clone())));
+        }
+        code.clone_from(replacement);
+    }
+    if !codes.contains(code.as_str()) {
+        issues.push(issue(Value::String(code.clone())));
+    }
+}
+
+// Normalize the date to RFC 3339, and report the date if it's invalid or has no timezone.
+fn normalize_date(
+    date: &mut String,
+    formats: &[String],
+    timezone: Option<FixedOffset>,
+    issue: impl Fn(IssueCode, Value) -> Issue,
+    issues: &mut Vec<Issue>,
+) {
+    if DateTime::parse_from_rfc3339(date).is_ok() {
+        return;
+    }
+
+    let localize = |naive: NaiveDateTime| timezone.and_then(|offset| offset.from_local_datetime(&naive).single());
+
+    // Dates like "2024-01-31" and "2024-01-31T12:00:00" are valid, except for the missing timezone.
+    let naive = date.parse::<NaiveDateTime>().ok().or_else(|| {
+        date.parse::<NaiveDate>()
+            .ok()
+            .map(|naive| naive.and_time(NaiveTime::MIN))
+    });
+
+    let (code, fix) = naive.map_or_else(
+        || {
+            let fix = formats.iter().find_map(|format| {
+                DateTime::parse_from_str(date, format)
+                    .ok()
+                    .or_else(|| NaiveDateTime::parse_from_str(date, format).ok().and_then(localize))
+                    .or_else(|| {
+                        NaiveDate::parse_from_str(date, format)
+                            .ok()
+                            .and_then(|naive| localize(naive.and_time(NaiveTime::MIN)))
+                    })
+            });
+            (IssueCode::Invalid, fix)
+        },
+        |naive| (IssueCode::NoTimezone, localize(naive)),
+    );
+
+    let issue = issue(code, Value::String(date.clone()));
+    if let Some(fix) = fix {
+        *date = fix.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        issues.push(issue.normalized(Value::String(date.clone())));
+    } else {
+        issues.push(issue);
+    }
+}
+
+const PATH_BID_AMOUNT: &str = "/bids/details[]/value/amount";
+const PATH_BID_CURRENCY: &str = "/bids/details[]/value/currency";
+const PATH_BID_ITEM_SCHEME: &str = "/bids/details[]/items[]/classification/scheme";
+const PATH_BID_STATUS: &str = "/bids/details[]/status";
+const PATH_AWARD_ITEM_SCHEME: &str = "/awards[]/items[]/classification/scheme";
+const PATH_AWARD_STATUS: &str = "/awards[]/status";
+const PATH_PARTY_ROLE: &str = "/parties[]/roles[]";
+const PATH_PROCUREMENT_METHOD: &str = "/tender/procurementMethod";
+const PATH_TENDER_STATUS: &str = "/tender/status";
+const PATH_CONTRACT_STATUS: &str = "/contracts[]/status";
+const PATH_TENDER_PERIOD_START_DATE: &str = "/tender/tenderPeriod/startDate";
+const PATH_TENDER_PERIOD_END_DATE: &str = "/tender/tenderPeriod/endDate";
+const PATH_BID_DATE: &str = "/bids/details[]/date";
+const PATH_AWARD_DATE: &str = "/awards[]/date";
+const PATH_CONTRACT_DATE_SIGNED: &str = "/contracts[]/dateSigned";
+// Rules don't count the fields that the command checks, to not count the same field twice.
+const PATHS_CHECKED: [&str; 15] = [
+    PATH_BID_AMOUNT,
+    PATH_BID_CURRENCY,
+    PATH_BID_ITEM_SCHEME,
+    PATH_BID_STATUS,
+    PATH_AWARD_ITEM_SCHEME,
+    PATH_AWARD_STATUS,
+    PATH_PARTY_ROLE,
+    PATH_PROCUREMENT_METHOD,
+    PATH_TENDER_STATUS,
+    PATH_CONTRACT_STATUS,
+    PATH_TENDER_PERIOD_START_DATE,
+    PATH_TENDER_PERIOD_END_DATE,
+    PATH_BID_DATE,
+    PATH_AWARD_DATE,
+    PATH_CONTRACT_DATE_SIGNED,
+];
+
 #[derive(Debug, Default)]
 pub struct Prepare;
 
@@ -511,17 +1272,26 @@ impl Prepare {
     // https://github.com/rust-lang/rust-clippy/issues/10413
     #[allow(clippy::significant_drop_tightening)]
     pub fn run<W: Write + Send>(
-        buffer: impl BufRead + Send,
+        input: impl Input,
         settings: Settings,
         output: &mut W,
         errors: &mut W,
-    ) -> Result<(), anyhow::Error> {
+        errors_format: ErrorsFormat,
+        errors_header: bool,
+    ) -> Result<Summary, anyhow::Error> {
         let empty_set: HashSet<String> = HashSet::new();
         let default_mapping = HashMap::new();
 
         let output = Job::new(BufWriter::new(output));
         let errors = Job::new(BufWriter::new(errors));
 
+        if errors_header && matches!(errors_format, ErrorsFormat::Csv) {
+            writeln!(
+                errors.new_task(),
+                "line,ocid,path,array indexes,incorrect value,error description"
+            )?;
+        }
+
         // [defaults]
         let defaults = settings.defaults.unwrap_or_default();
         let currency_default = defaults.currency.map(Value::String);
@@ -529,6 +1299,13 @@ impl Prepare {
         let bid_status_default = defaults.bid_status.map(Value::String);
         let award_status_default = defaults.award_status.map(Value::String);
         let party_roles_default = defaults.party_roles.unwrap_or_default();
+        let timezone = match defaults.timezone {
+            Some(timezone) => match timezone.parse::<FixedOffset>() {
+                Ok(offset) => Some(offset),
+                Err(e) => bail!("defaults.timezone: must be an offset, like -05:00 [{e}]"),
+            },
+            None => None,
+        };
 
         // [redactions]
         let redactions = settings.redactions.unwrap_or_default();
@@ -554,38 +1331,60 @@ impl Prepare {
         let binding = modifications.prefix_tenderer_or_supplier_id.unwrap_or_default();
         let prefix_tenderer_or_supplier_id = binding.as_str();
         let split_procurement_method_details = modifications.split_procurement_method_details;
+        let date_formats = modifications
+            .date_formats
+            .unwrap_or_default()
+            .split_terminator('|')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
 
         // [codelists.*]
         let codelists = settings.codelists.unwrap_or_default();
         let bid_status = codelists.get(&Codelist::BidStatus).unwrap_or(&default_mapping);
         let award_status = codelists.get(&Codelist::AwardStatus).unwrap_or(&default_mapping);
+        let procurement_method = codelists.get(&Codelist::ProcurementMethod).unwrap_or(&default_mapping);
+        let tender_status = codelists.get(&Codelist::TenderStatus).unwrap_or(&default_mapping);
+        let contract_status = codelists.get(&Codelist::ContractStatus).unwrap_or(&default_mapping);
+        let party_role = codelists.get(&Codelist::PartyRole).unwrap_or(&default_mapping);
+
+        // [rules.*]
+        let compiled_rules = Compiled::from_settings(settings.rules.unwrap_or_default())?;
 
-        let result = buffer
-            .lines()
-            .enumerate()
+        let lines = input.numbered_lines()?;
+        let result = lines
             .par_bridge()
-            .try_for_each(|(i, lines)| -> Result<(), anyhow::Error> {
+            .try_fold(Summary::default, |mut summary, (location, lines)| -> Result<Summary> {
                 // Use guard clauses to reduce indentation and ease readabaility.
                 let string = match lines {
                     Ok(string) => string,
-                    Err(e) => return Ok(warn!("Line {} caused an I/O error, skipping. [{e}]", i + 1)),
+                    Err(e) if InputError::is(&e) => return Err(input_error(&location, e)),
+                    Err(e) => {
+                        warn!("Line {location} caused an I/O error, skipping. [{e}]");
+                        return Ok(summary);
+                    }
                 };
 
                 let mut value: Value = match serde_json::from_str(&string) {
                     Ok(value) => value,
                     Err(e) => {
                         if !string.as_bytes().iter().all(u8::is_ascii_whitespace) {
-                            warn!("Line {} is invalid JSON, skipping. [{e}]", i + 1);
+                            warn!("Line {location} is invalid JSON, skipping. [{e}]");
                         }
-                        return Ok(());
+                        return Ok(summary);
                     }
                 };
 
                 let Some(release) = value.as_object_mut() else {
-                    return Ok(warn!("Line {} is not a JSON object, skipping.", i + 1));
+                    warn!("Line {location} is not a JSON object, skipping.");
+                    return Ok(summary);
                 };
 
-                let mut rows = csv::Writer::from_writer(errors.new_task());
+                let mut issues = vec![];
+
+                // Apply rules first, so that corrected fields are prepared like other fields.
+                for rule in &compiled_rules {
+                    rule.apply(release, &mut summary, &mut issues);
+                }
 
                 let mut award_id_contracts_cancelled = HashMap::new();
 
@@ -604,6 +1403,21 @@ impl Prepare {
                     ""
                 );
 
+                if let Some(Value::Array(parties)) = release.get_mut("parties") {
+                    for (j, party) in parties.iter_mut().enumerate() {
+                        if let Some(Value::Array(roles)) = party.get_mut("roles") {
+                            for (k, role) in roles.iter_mut().enumerate() {
+                                if let Value::String(role) = role {
+                                    summary.check(PATH_PARTY_ROLE);
+                                    let issue =
+                                        |value| Issue::new(PATH_PARTY_ROLE, vec![j, k], IssueCode::Invalid, value);
+                                    remap(role, party_role, &PARTY_ROLE, issue, &mut issues);
+                                }
+                            }
+                        }
+                    }
+                }
+
                 let mut party_roles_lookup = HashMap::new();
                 if party_roles_default {
                     if let Some(Value::Array(parties)) = release.get("parties") {
@@ -651,6 +1465,30 @@ impl Prepare {
                         *procurement_method_details =
                             procurement_method_details.split(pat).next().unwrap().trim_end().into();
                     }
+
+                    if let Some(Value::String(method)) = tender.get_mut("procurementMethod") {
+                        summary.check(PATH_PROCUREMENT_METHOD);
+                        let issue = |value| Issue::new(PATH_PROCUREMENT_METHOD, vec![], IssueCode::Invalid, value);
+                        remap(method, procurement_method, &PROCUREMENT_METHOD, issue, &mut issues);
+                    }
+                    if let Some(Value::String(status)) = tender.get_mut("status") {
+                        summary.check(PATH_TENDER_STATUS);
+                        let issue = |value| Issue::new(PATH_TENDER_STATUS, vec![], IssueCode::Invalid, value);
+                        remap(status, tender_status, &TENDER_STATUS, issue, &mut issues);
+                    }
+
+                    if let Some(Value::Object(tender_period)) = tender.get_mut("tenderPeriod") {
+                        for (path, field) in [
+                            (PATH_TENDER_PERIOD_START_DATE, "startDate"),
+                            (PATH_TENDER_PERIOD_END_DATE, "endDate"),
+                        ] {
+                            if let Some(Value::String(date)) = tender_period.get_mut(field) {
+                                summary.check(path);
+                                let issue = |code, value| Issue::new(path, vec![], code, value);
+                                normalize_date(date, &date_formats, timezone, issue, &mut issues);
+                            }
+                        }
+                    }
                 }
 
                 // /auctions
@@ -682,63 +1520,73 @@ impl Prepare {
                 {
                     for (j, bid) in details.iter_mut().enumerate() {
                         if let Some(Value::Object(value)) = bid.get_mut("value") {
-                            if let Some(Value::Number(amount)) = value.get("amount")
-                                && let Some(amount) = amount.as_f64()
+                            if let Some(Value::Number(number)) = value.get("amount")
+                                && let Some(amount) = number.as_f64()
                             {
+                                summary.check(PATH_BID_AMOUNT);
                                 if redact_amount
                                     .binary_search_by(|probe| probe.partial_cmp(&amount).unwrap())
                                     .is_ok()
                                 {
                                     value.remove("amount");
                                 } else if amount == 0.0 {
-                                    rows.serialize((i + 1, &ocid, "/bids/details[]/value/amount", j, "", "is zero"))?;
+                                    let value = Value::Number(number.clone());
+                                    issues.push(Issue::new(PATH_BID_AMOUNT, vec![j], IssueCode::IsZero, value));
                                 }
                             }
+                            summary.check(PATH_BID_CURRENCY);
                             if !value.contains_key("currency") {
+                                let issue = Issue::new(PATH_BID_CURRENCY, vec![j], IssueCode::NotSet, Value::Null);
                                 if let Some(default) = &currency_default {
                                     value.insert("currency".into(), default.clone());
+                                    issues.push(issue.fixed(default.clone()));
                                 } else {
-                                    rows.serialize((i + 1, &ocid, "/bids/details[]/value/currency", j, "", "not set"))?;
+                                    issues.push(issue);
                                 }
                             }
                         }
 
                         if let Some(Value::Array(items)) = bid.get_mut("items") {
                             for (k, item) in items.iter_mut().enumerate() {
-                                if let Some(Value::Object(classification)) = item.get_mut("classification")
-                                    && !classification.contains_key("scheme")
-                                {
-                                    if let Some(default) = &item_classification_scheme_default {
-                                        classification.insert("scheme".into(), default.clone());
-                                    } else {
-                                        rows.serialize((
-                                            i + 1,
-                                            &ocid,
-                                            "/bids/details[]/items[]/classification/scheme",
-                                            format!("{j}.{k}"),
-                                            "",
-                                            "not set",
-                                        ))?;
+                                if let Some(Value::Object(classification)) = item.get_mut("classification") {
+                                    summary.check(PATH_BID_ITEM_SCHEME);
+                                    if !classification.contains_key("scheme") {
+                                        let issue = Issue::new(
+                                            PATH_BID_ITEM_SCHEME,
+                                            vec![j, k],
+                                            IssueCode::NotSet,
+                                            Value::Null,
+                                        );
+                                        if let Some(default) = &item_classification_scheme_default {
+                                            classification.insert("scheme".into(), default.clone());
+                                            issues.push(issue.fixed(default.clone()));
+                                        } else {
+                                            issues.push(issue);
+                                        }
                                     }
                                 }
                             }
                         }
 
                         // is_none() is used instead of !contains_key(), as bid is a Value, not a Map.
+                        summary.check(PATH_BID_STATUS);
                         if bid.get("status").is_none() {
+                            let issue = Issue::new(PATH_BID_STATUS, vec![j], IssueCode::NotSet, Value::Null);
                             if let Some(default) = &bid_status_default {
                                 bid["status"] = default.clone();
+                                issues.push(issue.fixed(default.clone()));
                             } else {
-                                rows.serialize((i + 1, &ocid, "/bids/details[]/status", j, "", "not set"))?;
+                                issues.push(issue);
                             }
                         }
                         if let Some(Value::String(status)) = bid.get_mut("status") {
-                            if bid_status.contains_key(status) {
-                                status.clone_from(&bid_status[status]);
-                            }
-                            if !BID_STATUS.contains(status.as_str()) {
-                                rows.serialize((i + 1, &ocid, "/bids/details[]/status", j, status, "invalid"))?;
-                            }
+                            let issue = |value| Issue::new(PATH_BID_STATUS, vec![j], IssueCode::Invalid, value);
+                            remap(status, bid_status, &BID_STATUS, issue, &mut issues);
+                        }
+                        if let Some(Value::String(date)) = bid.get_mut("date") {
+                            summary.check(PATH_BID_DATE);
+                            let issue = |code, value| Issue::new(PATH_BID_DATE, vec![j], code, value);
+                            normalize_date(date, &date_formats, timezone, issue, &mut issues);
                         }
 
                         prepare_id_array!(
@@ -753,6 +1601,21 @@ impl Prepare {
                 }
 
                 // /contracts
+                if let Some(Value::Array(contracts)) = release.get_mut("contracts") {
+                    for (j, contract) in contracts.iter_mut().enumerate() {
+                        if let Some(Value::String(status)) = contract.get_mut("status") {
+                            summary.check(PATH_CONTRACT_STATUS);
+                            let issue = |value| Issue::new(PATH_CONTRACT_STATUS, vec![j], IssueCode::Invalid, value);
+                            remap(status, contract_status, &CONTRACT_STATUS, issue, &mut issues);
+                        }
+                        if let Some(Value::String(date)) = contract.get_mut("dateSigned") {
+                            summary.check(PATH_CONTRACT_DATE_SIGNED);
+                            let issue = |code, value| Issue::new(PATH_CONTRACT_DATE_SIGNED, vec![j], code, value);
+                            normalize_date(date, &date_formats, timezone, issue, &mut issues);
+                        }
+                    }
+                }
+
                 if award_status_by_contract_status && let Some(Value::Array(contracts)) = release.get_mut("contracts") {
                     for contract in &mut *contracts {
                         stringify!(contract, "awardID");
@@ -783,40 +1646,45 @@ impl Prepare {
                                     ""
                                 );
 
-                                if let Some(Value::Object(classification)) = item.get_mut("classification")
-                                    && !classification.contains_key("scheme")
-                                {
-                                    if let Some(default) = &item_classification_scheme_default {
-                                        classification.insert("scheme".into(), default.clone());
-                                    } else {
-                                        rows.serialize((
-                                            i + 1,
-                                            &ocid,
-                                            "/awards[]/items[]/classification/scheme",
-                                            format!("{j}.{k}"),
-                                            "",
-                                            "not set",
-                                        ))?;
+                                if let Some(Value::Object(classification)) = item.get_mut("classification") {
+                                    summary.check(PATH_AWARD_ITEM_SCHEME);
+                                    if !classification.contains_key("scheme") {
+                                        let issue = Issue::new(
+                                            PATH_AWARD_ITEM_SCHEME,
+                                            vec![j, k],
+                                            IssueCode::NotSet,
+                                            Value::Null,
+                                        );
+                                        if let Some(default) = &item_classification_scheme_default {
+                                            classification.insert("scheme".into(), default.clone());
+                                            issues.push(issue.fixed(default.clone()));
+                                        } else {
+                                            issues.push(issue);
+                                        }
                                     }
                                 }
                             }
                         }
 
                         // is_none() is used instead of !contains_key(), as award is a Value, not a Map.
+                        summary.check(PATH_AWARD_STATUS);
                         if award.get("status").is_none() {
+                            let issue = Issue::new(PATH_AWARD_STATUS, vec![j], IssueCode::NotSet, Value::Null);
                             if let Some(default) = &award_status_default {
                                 award["status"] = default.clone();
+                                issues.push(issue.fixed(default.clone()));
                             } else {
-                                rows.serialize((i + 1, &ocid, "/awards[]/status", j, "", "not set"))?;
+                                issues.push(issue);
                             }
                         }
                         if let Some(Value::String(status)) = award.get_mut("status") {
-                            if award_status.contains_key(status) {
-                                status.clone_from(&award_status[status]);
-                            }
-                            if !AWARD_STATUS.contains(status.as_str()) {
-                                rows.serialize((i + 1, &ocid, "/awards[]/status", j, status, "invalid"))?;
-                            }
+                            let issue = |value| Issue::new(PATH_AWARD_STATUS, vec![j], IssueCode::Invalid, value);
+                            remap(status, award_status, &AWARD_STATUS, issue, &mut issues);
+                        }
+                        if let Some(Value::String(date)) = award.get_mut("date") {
+                            summary.check(PATH_AWARD_DATE);
+                            let issue = |code, value| Issue::new(PATH_AWARD_DATE, vec![j], code, value);
+                            normalize_date(date, &date_formats, timezone, issue, &mut issues);
                         }
                         if award_status_by_contract_status
                             && let Some(Value::String(id)) = award.get("id")
@@ -851,10 +1719,14 @@ impl Prepare {
                     }
                 }
 
+                write_issues(errors.new_task(), &issues, &location, &ocid, errors_format)?;
                 writeln!(output.new_task(), "{}", &serde_json::to_string(&release)?)?;
 
-                Ok(())
-            });
+                summary.add(&issues);
+
+                Ok(summary)
+            })
+            .try_reduce(Summary::default, |a, b| Ok(a.merge(b)));
 
         // Buffers flush when dropped, but any errors are ignored. Flush explicitly to raise errors.
         output.new_task().flush()?;
@@ -864,6 +1736,139 @@ impl Prepare {
     }
 }
 
+// Merge the source object into the target object. The source's null values remove the target's fields.
+fn merge(target: &mut Map<String, Value>, source: Map<String, Value>) {
+    for (key, value) in source {
+        match value {
+            Value::Null => {
+                target.shift_remove(&key);
+            }
+            Value::Object(object) => {
+                if let Some(Value::Object(existing)) = target.get_mut(&key) {
+                    merge(existing, object);
+                } else {
+                    let mut new = Map::new();
+                    merge(&mut new, object);
+                    target.insert(key, Value::Object(new));
+                }
+            }
+            // Arrays of objects with identifiers are merged by identifier. Other arrays are replaced.
+            Value::Array(array)
+                if !array.is_empty()
+                    && !WHOLE_LIST_MERGE.contains(key.as_str())
+                    && array.iter().all(|v| v.get("id").is_some_and(|id| !id.is_null())) =>
+            {
+                let existing = match target.get_mut(&key) {
+                    Some(Value::Array(existing)) => std::mem::take(existing),
+                    _ => vec![],
+                };
+                target.insert(key, Value::Array(merge_by_id(existing, array)));
+            }
+            _ => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_by_id(existing: Vec<Value>, source: Vec<Value>) -> Vec<Value> {
+    // Identifiers are compared as JSON text, so that 1 and "1" are different.
+    let mut values: IndexMap<String, Value> = IndexMap::new();
+
+    for (i, value) in existing.into_iter().enumerate() {
+        // A previous release can have set the array without identifiers. Keep its elements, in their positions.
+        // The key isn't JSON text, so it can't match an identifier.
+        let key = match value.get("id") {
+            Some(id) if !id.is_null() => id.to_string(),
+            _ => format!("#{i}"),
+        };
+        values.insert(key, value);
+    }
+    for value in source {
+        if let Value::Object(object) = value
+            && let Value::Object(target) = values
+                .entry(object["id"].to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+        {
+            merge(target, object);
+        }
+    }
+
+    values.into_values().collect()
+}
+
+#[derive(Debug, Default)]
+pub struct Compile;
+
+impl Compile {
+    /// Merge the individual releases of each contracting process into a compiled release, and write the compiled
+    /// releases as line-delimited JSON, ordered by ocid.
+    ///
+    /// All releases are read into memory, because releases for the same ocid can be anywhere in the input.
+    ///
+    /// # Errors
+    ///
+    pub fn run<W: Write + Send>(input: impl Input, output: &mut W) -> Result<(), anyhow::Error> {
+        let mut groups = fold_reduce(
+            input,
+            IndexMap::<String, Vec<(usize, Map<String, Value>)>>::new,
+            |mut item, value, position| {
+                if let Value::Object(release) = value
+                    && let Some(Value::String(ocid)) = release.get("ocid")
+                {
+                    item.entry(ocid.clone()).or_default().push((position, release));
+                } else {
+                    warn!("Release has no ocid, skipping.");
+                }
+                item
+            },
+            |mut item, other| {
+                for (ocid, releases) in other {
+                    item.entry(ocid).or_default().extend(releases);
+                }
+                item
+            },
+            Ok,
+        )?;
+
+        groups.sort_keys();
+
+        let mut writer = BufWriter::new(output);
+
+        for (ocid, mut releases) in groups {
+            // Releases without a valid date are merged first. Releases with the same date are merged in input order,
+            // so that later releases win.
+            releases.sort_by_cached_key(|(position, release)| {
+                let date = release.get("date").and_then(Value::as_str);
+                let parsed = date.and_then(|date| DateTime::<FixedOffset>::parse_from_rfc3339(date).ok());
+                if parsed.is_none() {
+                    warn!("A release of {ocid} has no valid date, merging it first.");
+                }
+                (parsed, *position)
+            });
+
+            let mut compiled = Map::new();
+            for (_, release) in releases {
+                merge(&mut compiled, release);
+            }
+
+            compiled.insert("tag".into(), Value::Array(vec![Value::String("compiled".into())]));
+            if let Some(Value::String(date)) = compiled.get("date") {
+                compiled.insert("id".into(), Value::String(format!("{ocid}-{date}")));
+            } else {
+                compiled.shift_remove("id");
+            }
+
+            serde_json::to_writer(&mut writer, &compiled)?;
+            writer.write_all(b"\n")?;
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Coverage {
     counts: IndexMap<String, u32>,
@@ -877,11 +1882,11 @@ impl Coverage {
     ///
     /// # Errors
     ///
-    pub fn run(buffer: impl BufRead + Send) -> Result<Self> {
+    pub fn run(input: impl Input) -> Result<Self> {
         fold_reduce(
-            buffer,
+            input,
             Self::default,
-            |mut item, value| {
+            |mut item, value, _| {
                 item.add(value, &mut Vec::with_capacity(16));
                 item
             },
@@ -984,6 +1989,26 @@ mod tests {
         assert_eq!(result.unwrap().counts, expected);
     }
 
+    #[rstest]
+    #[case("gz")]
+    #[case("bz2")]
+    #[case("zst")]
+    fn decompress_short_reads(#[case] extension: &str) {
+        // Like a pipe, return one byte at a time.
+        let file = File::open(format!("tests/fixtures/compressed/base_array.jsonl.{extension}")).unwrap();
+        let mut output = String::new();
+
+        decompress(BufReader::with_capacity(1, file))
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+
+        assert_eq!(
+            output,
+            std::fs::read_to_string("tests/fixtures/coverage/base_array.jsonl").unwrap()
+        );
+    }
+
     fn check_prepare(name: &str) {
         let mut output = vec![];
         let mut errors = vec![];
@@ -992,9 +2017,16 @@ mod tests {
 
         // Same as main.rs.
         let config = Config::builder().add_source(source).build().unwrap();
-        let settings = serde_path_to_error::deserialize(config).unwrap();
-
-        let result = Prepare::run(reader(name, "jsonl"), settings, &mut output, &mut errors);
+        let settings: Settings = serde_path_to_error::deserialize(config).unwrap();
+
+        let result = Prepare::run(
+            reader(name, "jsonl"),
+            settings.clone(),
+            &mut output,
+            &mut errors,
+            ErrorsFormat::Csv,
+            false,
+        );
 
         let mut expected_output = String::new();
         reader(name, "output").read_to_string(&mut expected_output).unwrap();
@@ -1006,6 +2038,44 @@ mod tests {
             assert_eq!(String::from_utf8(errors).unwrap(), expected_errors);
         }
 
+        if basedir.join(format!("{name}.summary")).exists() {
+            let mut summary = vec![];
+            result.as_ref().unwrap().write(&mut summary).unwrap();
+
+            let mut expected_summary = String::new();
+            reader(name, "summary").read_to_string(&mut expected_summary).unwrap();
+            assert_eq!(String::from_utf8(summary).unwrap(), expected_summary);
+        }
+
+        if basedir.join(format!("{name}.issues")).exists() {
+            let mut issues = vec![];
+            let result = Prepare::run(
+                reader(name, "jsonl"),
+                settings,
+                &mut vec![],
+                &mut issues,
+                ErrorsFormat::Jsonl,
+                false,
+            );
+
+            let mut expected_issues = String::new();
+            reader(name, "issues").read_to_string(&mut expected_issues).unwrap();
+            assert_eq!(String::from_utf8(issues).unwrap(), expected_issues);
+            assert!(result.is_ok());
+        }
+
+        assert!(result.is_ok());
+    }
+
+    fn check_compile(name: &str) {
+        let mut output = vec![];
+
+        let result = Compile::run(reader(name, "jsonl"), &mut output);
+
+        let mut expected = String::new();
+        reader(name, "expected").read_to_string(&mut expected).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
         assert!(result.is_ok());
     }
 
@@ -1088,5 +2158,239 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rows() {
+        let data = json!({
+            "OCID": {"F": {"R024": 0.01}},
+            "Buyer": {"B": {"R038": 0.5}},
+            "Tenderer": {"T": {"R025": 0.2, "R024": 0.0}},
+            "Meta": {"R024": {"q1": 0.5}},
+            "Maps": {
+                "ocid_buyer_r038": {"F": "B", "X": "B"},
+                "ocid_tenderer": {"F": ["T"], "X": ["T", "Y"]},
+                "ocid_tenderer_r024": {"F": ["T"]}
+            }
+        });
+
+        let item = Indicators::from_reader(data.to_string().as_bytes()).unwrap();
+        let mut writer = csv::Writer::from_writer(vec![]);
+        for row in item.rows("2000-01-01T00:00:00Z") {
+            writer.serialize(row).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "\
+ocid,subject,code,result,buyer_id,procuring_entity_id,tenderer_id,created_at
+F,OCID,R024,0.01,,,,2000-01-01T00:00:00Z
+F,Buyer,R038,0.5,B,,,2000-01-01T00:00:00Z
+X,Buyer,R038,0.5,B,,,2000-01-01T00:00:00Z
+F,Tenderer,R024,0.0,,,T,2000-01-01T00:00:00Z
+F,Tenderer,R025,0.2,,,T,2000-01-01T00:00:00Z
+X,Tenderer,R025,0.2,,,T,2000-01-01T00:00:00Z
+"
+        );
+    }
+
+    #[test]
+    fn exchange_rates() {
+        let settings = Settings {
+            currency: Some("USD".into()),
+            exchange_rates: Some("tests/fixtures/exchange_rates/rates.csv".into()),
+            R036: Some(indicators::Empty {}),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("exchange_rates/R036", "jsonl"), settings, &false).unwrap();
+        let expected: IndexMap<Group, IndexMap<String, HashMap<Indicator, f64>>> =
+            serde_json::from_reader(reader("exchange_rates/R036", "expected")).unwrap();
+
+        assert_eq!(result.results, expected);
+        assert_eq!(
+            serde_json::to_value(&result.meta["exchange_rates"]).unwrap(),
+            json!({"converted": 3.0, "skipped": 2.0})
+        );
+    }
+
+    #[test]
+    fn exchange_rates_awards() {
+        let settings = Settings {
+            currency: Some("USD".into()),
+            exchange_rates: Some("tests/fixtures/exchange_rates/rates.csv".into()),
+            R006: Some(indicators::R006 {
+                threshold: Some(1000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("exchange_rates/R006", "jsonl"), settings, &false).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&result.results).unwrap(),
+            json!({"OCID": {"date": {"R006": 1200.0}}, "Buyer": {"B": {"R006": 1.0}}, "Tenderer": {"S": {"R006": 1.0}}})
+        );
+        assert_eq!(
+            serde_json::to_value(&result.meta["exchange_rates"]).unwrap(),
+            json!({"converted": 2.0, "skipped": 1.0})
+        );
+    }
+
+    #[test]
+    fn exchange_rates_without_currency() {
+        let settings = Settings {
+            exchange_rates: Some("tests/fixtures/exchange_rates/rates.csv".into()),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("exchange_rates/R036", "jsonl"), settings, &false);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "exchange_rates: currency must be set, to convert to the main currency"
+        );
+    }
+
+    #[test]
+    fn r011_threshold() {
+        let settings = Settings {
+            R011: Some(indicators::R011 {
+                procurement_threshold: Some(1000),
+                threshold: Some(3),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(
+            reader("indicators/R011-procurement_threshold-1000", "jsonl"),
+            settings,
+            &false,
+        )
+        .unwrap();
+
+        // H's share is a high outlier, but the share isn't used if the threshold is configured.
+        assert_eq!(
+            serde_json::to_value(&result.results).unwrap(),
+            json!({"Buyer": {"F": {"R011": 5.0}}})
+        );
+    }
+
+    #[test]
+    fn r011_map() {
+        let settings = Settings {
+            R011: Some(indicators::R011 {
+                procurement_threshold: Some(1000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(
+            reader("indicators/R011-procurement_threshold-1000", "jsonl"),
+            settings,
+            &true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&result.maps.ocid_buyer_r011).unwrap(),
+            json!({"F-1": "F", "F-2": "F", "F-3": "F", "F-4": "F", "F-5": "F", "H-12": "H"})
+        );
+    }
+
+    #[test]
+    fn r011_map_ties() {
+        let settings = Settings {
+            R011: Some(indicators::R011 {
+                procurement_threshold: Some(1000),
+                threshold: Some(2),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("r011/ties", "jsonl"), settings, &true).unwrap();
+
+        // The 43 and 44 prefixes have the same number of awards. The first prefix is used.
+        assert_eq!(
+            serde_json::to_value(&result.maps.ocid_buyer_r011).unwrap(),
+            json!({"Q-2": "Q", "Q-4": "Q"})
+        );
+    }
+
+    #[test]
+    fn r031_procurement_method_details() {
+        let settings = Settings {
+            R031: Some(indicators::R031 {
+                procurement_method_details: Some(HashMap::from([
+                    (String::from("x"), String::from("5|")),
+                    (String::from("y"), String::from("31|100")),
+                    (String::from("n"), String::from("invalid")),
+                ])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("indicators/R031", "jsonl"), settings, &false).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&result.results).unwrap(),
+            json!({"OCID": {"x long": {"R031": 100.0}, "x short": {"R031": 0.0}, "y1": {"R031": 30.0}, "n long": {"R031": 60.0}}})
+        );
+    }
+
+    #[test]
+    fn registration_dates() {
+        let settings = Settings {
+            R043: Some(indicators::R043 {
+                registration_dates: Some("tests/fixtures/registration_dates/dates.csv".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("registration_dates/R043", "jsonl"), settings, &false).unwrap();
+        let expected: IndexMap<Group, IndexMap<String, HashMap<Indicator, f64>>> =
+            serde_json::from_reader(reader("registration_dates/R043", "expected")).unwrap();
+
+        assert_eq!(result.results, expected);
+    }
+
+    #[test]
+    fn registration_dates_invalid_date() {
+        let settings = Settings {
+            R043: Some(indicators::R043 {
+                registration_dates: Some("tests/fixtures/registration_dates/invalid.csv".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("registration_dates/R043", "jsonl"), settings, &false);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "registration_dates: tests/fixtures/registration_dates/invalid.csv: row 3 has an invalid date [input contains invalid characters]"
+        );
+    }
+
+    #[test]
+    fn r071_amended() {
+        let settings = Settings {
+            R071: Some(indicators::FloatThreshold::default()),
+            ..Default::default()
+        };
+
+        let result = Indicators::run(reader("indicators/R071", "jsonl"), settings, &false).unwrap();
+
+        // "amended decrease" has no increase, and "amended" isn't flagged by the fence.
+        assert_eq!(
+            serde_json::to_value(&result.meta["R071_amended"]).unwrap(),
+            json!({"amended": 0.05})
+        );
+    }
+
     include!(concat!(env!("OUT_DIR"), "/lib.include"));
 }
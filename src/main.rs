@@ -1,13 +1,15 @@
-use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
+use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::error::ErrorKind;
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use config::{Config, ConfigError};
 use human_panic::setup_panic;
 use log::LevelFilter;
+use ocdscardinal::{ErrorsFormat, Files, InputFormat};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -31,18 +33,48 @@ enum Commands {
     /// The "" path corresponds to a line. A path ending with / corresponds to an object. A path ending with []
     /// corresponds to an array element. Other paths correspond to object members.
     Coverage {
-        /// The path to the file (or "-" for standard input), in which each line is JSON text
-        file: PathBuf,
+        /// The paths to the files or directories (or "-" for standard input), in which each line is JSON text
+        #[arg(required = true)]
+        file: Vec<PathBuf>,
+        /// The format of the input
+        #[arg(long, value_enum, default_value_t = InputFormat::Jsonl)]
+        input_format: InputFormat,
+    },
+    /// Merge OCDS individual releases into compiled releases, from a line-delimited JSON file
+    ///
+    /// Releases are grouped by ocid and sorted by date, then merged according to the OCDS merge routine.
+    ///
+    /// Compiled releases are written to the --output file as line-delimited JSON, ordered by ocid.
+    Compile {
+        /// The paths to the files or directories (or "-" for standard input), in which each line is a release as
+        /// JSON text
+        #[arg(required = true)]
+        file: Vec<PathBuf>,
+        /// The format of the input
+        #[arg(long, value_parser = release_format_parser(), default_value = "jsonl")]
+        input_format: InputFormat,
+        /// The file to which to write compiled releases (or "-" for standard output)
+        #[arg(long, short)]
+        output: PathBuf,
     },
     /// Correct quality issues within OCDS compiled releases in a line-delimited JSON file
     ///
     /// Corrected data is written to standard output as line-delimited JSON.
     ///
     /// Quality issues are written to standard error as CSV rows with the columns: line, ocid, path, array
-    /// indexes, incorrect value, error description.
+    /// indexes, incorrect value, error description. If there are multiple input files, the line is prefixed
+    /// by the file path and a colon.
+    ///
+    /// If --errors-format jsonl is set, quality issues are instead written as line-delimited JSON, including
+    /// issues that were fixed (for example, by a default value).
     Prepare {
-        /// The path to the file (or "-" for standard input), in which each line is a contracting process as JSON text
-        file: PathBuf,
+        /// The paths to the files or directories (or "-" for standard input), in which each line is a contracting
+        /// process as JSON text
+        #[arg(required = true)]
+        file: Vec<PathBuf>,
+        /// The format of the input
+        #[arg(long, value_enum, default_value_t = InputFormat::Jsonl)]
+        input_format: InputFormat,
         /// The path to the settings file
         #[arg(long, short, value_parser = settings_parser)]
         settings: Option<ocdscardinal::Settings>,
@@ -52,6 +84,15 @@ enum Commands {
         /// The file to which to write quality issues (or "-" for standard output)
         #[arg(long, short)]
         errors: PathBuf,
+        /// The format in which to write quality issues
+        #[arg(long, value_enum, default_value_t = ErrorsFormat::Csv)]
+        errors_format: ErrorsFormat,
+        /// Write a header row to the CSV file of quality issues
+        #[arg(long, default_value_t = false)]
+        errors_header: bool,
+        /// The file to which to write a summary of quality issues, as a CSV table (or "-" for standard output)
+        #[arg(long)]
+        summary: Option<PathBuf>,
     },
     /// Calculate procurement indicators from OCDS compiled releases in a line-delimited JSON file
     ///
@@ -64,9 +105,16 @@ enum Commands {
     ///
     /// If --map is set, the result has a "Maps" key, with mappings from contracting processes to
     /// organizations.
+    ///
+    /// If --format csv is set, the result is instead written as CSV rows, like the results-to-csv command.
     Indicators {
-        /// The path to the file (or "-" for standard input), in which each line is a contracting process as JSON text
-        file: PathBuf,
+        /// The paths to the files or directories (or "-" for standard input), in which each line is a contracting
+        /// process as JSON text
+        #[arg(required = true)]
+        file: Vec<PathBuf>,
+        /// The format of the input
+        #[arg(long, value_enum, default_value_t = InputFormat::Jsonl)]
+        input_format: InputFormat,
         /// The path to the settings file
         #[arg(long, short, value_parser = settings_parser)]
         settings: Option<ocdscardinal::Settings>,
@@ -79,6 +127,29 @@ enum Commands {
         /// Include the "Maps" key, mapping contracting processes to organizations
         #[arg(long, default_value_t = false)]
         map: bool,
+        /// The format of the results
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+    },
+    /// Convert the JSON output of the indicators command to CSV rows, appending to a CSV file
+    ///
+    /// The JSON output must have a "Maps" key (see the --map option of the indicators command).
+    ///
+    /// Each row is a result for a contracting process, with the columns: ocid, subject (the group of the
+    /// result), code, result, buyer ID, procuring entity ID, tenderer ID, and creation time. If the group
+    /// is `Buyer`, `ProcuringEntity` or `Tenderer`, the matching ID column is set.
+    ///
+    /// If the CSV file exists, rows with the same ocid, code and organization ID as an existing row are
+    /// skipped. Otherwise, the CSV file is created with a header row.
+    ResultsToCsv {
+        /// The path to the file (or "-" for standard input), containing the JSON output of the indicators command
+        file: PathBuf,
+        /// The CSV file to which to append rows (or "-" for standard output)
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Don't print the number of rows written to standard error
+        #[arg(long, short, default_value_t = false)]
+        quiet: bool,
     },
     /// Write a default settings file for configuration.
     Init {
@@ -90,12 +161,31 @@ enum Commands {
     },
 }
 
+#[derive(Clone, ValueEnum)]
+enum Format {
+    /// A JSON object
+    Json,
+    /// CSV rows, with a header row
+    Csv,
+}
+
 fn file_argument_error(file: &Path, message: &str) -> ! {
     Cli::command()
         .error(ErrorKind::ValueValidation, format!("{}: {message}", file.display()))
         .exit()
 }
 
+// Record packages are excluded, because records have compiled releases, already.
+fn release_format_parser() -> impl TypedValueParser<Value = InputFormat> {
+    PossibleValuesParser::new(
+        InputFormat::value_variants()
+            .iter()
+            .filter(|format| !matches!(format, InputFormat::RecordPackage))
+            .filter_map(ValueEnum::to_possible_value),
+    )
+    .map(|value| InputFormat::from_str(&value, false).expect("possible value"))
+}
+
 fn settings_parser(s: &str) -> Result<ocdscardinal::Settings, ConfigError> {
     let config = Config::builder().add_source(config::File::with_name(s)).build()?;
     serde_path_to_error::deserialize(config).map_err(|error| match error.inner() {
@@ -124,6 +214,78 @@ fn reader(file: &PathBuf) -> BufReader<Box<dyn Read + Send>> {
     }
 }
 
+// Recurse into directories, collecting files whose names contain ".jsonl" (like ".jsonl.gz"), in sorted order.
+fn walk(directory: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort_unstable();
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, paths)?;
+        } else if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().contains(".jsonl"))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+enum Source {
+    Stdin(Box<dyn BufRead + Send>),
+    Files(Files),
+}
+
+impl Source {
+    fn new(files: &[PathBuf], format: InputFormat) -> Self {
+        let stdin = PathBuf::from("-");
+
+        if files == [stdin.clone()] {
+            return Self::Stdin(
+                format
+                    .reader(reader(&stdin))
+                    .unwrap_or_else(|e| file_argument_error(&stdin, &e.to_string())),
+            );
+        }
+
+        let mut paths = vec![];
+        for file in files {
+            if file == &stdin {
+                file_argument_error(file, "Standard input can't be combined with files");
+            }
+            if file.is_dir() {
+                let length = paths.len();
+                if let Err(e) = walk(file, &mut paths) {
+                    file_argument_error(file, &e.to_string());
+                }
+                if paths.len() == length {
+                    file_argument_error(file, "Contains no *.jsonl* files");
+                }
+            } else {
+                // Report errors before reading any file. Files are opened again when read.
+                if let Err(e) = File::open(file) {
+                    file_argument_error(file, &e.to_string());
+                }
+                paths.push(file.clone());
+            }
+        }
+
+        Self::Files(Files::new(paths, format))
+    }
+}
+
+// Dispatch to the run function, which is generic over the input.
+macro_rules! run {
+    ( $source:expr , $function:path $( , $arg:expr )* ) => {
+        match $source {
+            Source::Stdin(input) => $function(input $( , $arg )*),
+            Source::Files(input) => $function(input $( , $arg )*),
+        }
+    };
+}
+
 fn create(file: &PathBuf) -> Box<dyn Write + Send> {
     if file == &PathBuf::from("-") {
         Box::new(io::stdout())
@@ -132,6 +294,7 @@ fn create(file: &PathBuf) -> Box<dyn Write + Send> {
     }
 }
 
+#[allow(clippy::too_many_lines)]
 fn main() {
     setup_panic!();
 
@@ -153,48 +316,110 @@ fn main() {
             Ok(false) => println!("Settings written to {file:?}."),
             Ok(true) => {} // written to standard output
         },
-        Commands::Coverage { file } => match ocdscardinal::Coverage::run(reader(file)) {
-            Ok(item) => println!("{:?}", item.results()),
-            Err(e) => application_error(&e),
-        },
+        Commands::Coverage { file, input_format } => {
+            match run!(Source::new(file, *input_format), ocdscardinal::Coverage::run) {
+                Ok(item) => println!("{:?}", item.results()),
+                Err(e) => application_error(&e),
+            }
+        }
+        Commands::Compile {
+            file,
+            input_format,
+            output,
+        } => {
+            if let Err(e) = run!(
+                Source::new(file, *input_format),
+                ocdscardinal::Compile::run,
+                &mut create(output)
+            ) {
+                application_error(&e);
+            }
+        }
         Commands::Prepare {
             file,
+            input_format,
             settings,
             output,
             errors,
+            errors_format,
+            errors_header,
+            summary,
         } => {
-            if let Err(e) = ocdscardinal::Prepare::run(
-                reader(file),
+            match run!(
+                Source::new(file, *input_format),
+                ocdscardinal::Prepare::run,
                 settings.clone().unwrap_or_default(),
                 &mut create(output),
                 &mut create(errors),
+                *errors_format,
+                *errors_header
             ) {
-                application_error(&e);
+                Ok(item) => {
+                    if let Some(summary) = summary {
+                        if let Err(e) = item.write(create(summary)) {
+                            application_error(&e);
+                        }
+                    }
+                }
+                Err(e) => application_error(&e),
             }
         }
         Commands::Indicators {
             file,
+            input_format,
             count,
             settings,
             no_meta,
             map,
-        } => match ocdscardinal::Indicators::run(reader(file), settings.clone().unwrap_or_default(), map) {
-            Ok(item) => {
-                let mut output = serde_json::to_value(item.results()).unwrap();
-                if !no_meta {
-                    output["Meta"] = serde_json::to_value(&item.meta).unwrap();
+            format,
+        } => {
+            let csv = matches!(format, Format::Csv);
+            // CSV rows are written using the "Maps" key.
+            match run!(
+                Source::new(file, *input_format),
+                ocdscardinal::Indicators::run,
+                settings.clone().unwrap_or_default(),
+                &(*map || csv)
+            ) {
+                Ok(item) if csv => {
+                    if let Err(e) = ocdscardinal::results_to_csv(&item, &PathBuf::from("-")) {
+                        application_error(&e);
+                    }
+                    if *count {
+                        for (group, subresults) in item.results() {
+                            eprintln!("{:?}: {:?}", group, subresults.len());
+                        }
+                    }
                 }
-                if *map {
-                    output["Maps"] = serde_json::to_value(&item.maps).unwrap();
+                Ok(item) => {
+                    let mut output = serde_json::to_value(item.results()).unwrap();
+                    if !no_meta {
+                        output["Meta"] = serde_json::to_value(&item.meta).unwrap();
+                    }
+                    if *map {
+                        output["Maps"] = serde_json::to_value(&item.maps).unwrap();
+                    }
+                    println!("{}", serde_json::to_string(&output).unwrap());
+                    if *count {
+                        for (group, subresults) in item.results() {
+                            eprintln!("{:?}: {:?}", group, subresults.len());
+                        }
+                    }
                 }
-                println!("{}", serde_json::to_string(&output).unwrap());
-                if *count {
-                    for (group, subresults) in item.results() {
-                        eprintln!("{:?}: {:?}", group, subresults.len());
+                Err(e) => application_error(&e),
+            }
+        }
+        Commands::ResultsToCsv { file, output, quiet } => {
+            match ocdscardinal::Indicators::from_reader(reader(file))
+                .and_then(|item| ocdscardinal::results_to_csv(&item, output))
+            {
+                Ok(rows) => {
+                    if !quiet {
+                        eprintln!("{rows} rows written to {output:?}.");
                     }
                 }
+                Err(e) => application_error(&e),
             }
-            Err(e) => application_error(&e),
-        },
+        }
     }
 }
@@ -0,0 +1,246 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use log::warn;
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+
+use crate::indicators::{Cast, Rule};
+use crate::{Issue, IssueCode, Summary, PATHS_CHECKED};
+
+/// A part of a rule's path, like "details[]".
+#[derive(Debug)]
+struct Segment {
+    key: String,
+    /// Whether the part is an array, like "details[]".
+    array: bool,
+}
+
+/// A rule from the `[rules.*]` sections of the settings file, ready to apply to compiled releases.
+#[derive(Debug)]
+pub struct Compiled {
+    /// The path to the field, in which `[]` is an array.
+    path: &'static str,
+    /// Whether to count the fields in the summary.
+    check: bool,
+    segments: Vec<Segment>,
+    delete: bool,
+    rename: Option<String>,
+    default: Option<Value>,
+    map: HashMap<String, String>,
+    pattern: Option<(Regex, String)>,
+    cast: Option<Cast>,
+}
+
+impl Compiled {
+    /// Validate the rules and return them in the order of their names.
+    ///
+    /// # Errors
+    ///
+    pub fn from_settings(rules: HashMap<String, Rule>) -> Result<Vec<Self>> {
+        let mut rules: Vec<_> = rules.into_iter().collect();
+        rules.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        rules.into_iter().map(|(name, rule)| Self::new(&name, rule)).collect()
+    }
+
+    fn new(name: &str, rule: Rule) -> Result<Self> {
+        let Some(path) = rule.path.strip_prefix('/') else {
+            bail!("rules.{name}: path must start with a slash, like /tender/title");
+        };
+
+        let mut segments = vec![];
+        for part in path.split('/') {
+            let (key, array) = part.strip_suffix("[]").map_or((part, false), |key| (key, true));
+            if key.is_empty() {
+                bail!("rules.{name}: path must not contain empty field names");
+            }
+            segments.push(Segment { key: key.into(), array });
+        }
+
+        let delete = rule.delete.unwrap_or_default();
+        let modifies = rule.map.is_some() || rule.pattern.is_some() || rule.cast.is_some();
+        if delete && (rule.rename.is_some() || rule.default.is_some() || modifies) {
+            bail!("rules.{name}: delete can't be combined with other operations");
+        }
+        if segments.last().is_some_and(|segment| segment.array) && (rule.rename.is_some() || rule.default.is_some()) {
+            bail!("rules.{name}: rename and default are not supported if the path ends with []");
+        }
+        if rule.replacement.is_some() && rule.pattern.is_none() {
+            bail!("rules.{name}: replacement requires a pattern");
+        }
+
+        let default = match (rule.default, rule.cast) {
+            (Some(default), Some(Cast::Number)) => match default.trim().parse::<Number>() {
+                Ok(number) => Some(Value::Number(number)),
+                Err(e) => bail!("rules.{name}: default is not a number [{e}]"),
+            },
+            (default, _) => default.map(Value::String),
+        };
+
+        let pattern = match rule.pattern {
+            Some(pattern) => match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, rule.replacement.unwrap_or_default())),
+                Err(e) => bail!("rules.{name}: pattern is not a valid regular expression [{e}]"),
+            },
+            None => None,
+        };
+
+        let check = !PATHS_CHECKED.contains(&rule.path.as_str());
+
+        Ok(Self {
+            // The rules are used until the program exits. Leak the path, to report issues like other fields.
+            path: rule.path.leak(),
+            check,
+            segments,
+            delete,
+            rename: rule.rename,
+            default,
+            map: rule.map.unwrap_or_default(),
+            pattern,
+            cast: rule.cast,
+        })
+    }
+
+    fn check(&self, summary: &mut Summary) {
+        if self.check {
+            summary.check(self.path);
+        }
+    }
+
+    /// Apply the rule to each field matching its path.
+    pub fn apply(&self, release: &mut Map<String, Value>, summary: &mut Summary, issues: &mut Vec<Issue>) {
+        self.walk(release, &self.segments, &mut vec![], summary, issues);
+    }
+
+    fn walk(
+        &self,
+        object: &mut Map<String, Value>,
+        segments: &[Segment],
+        indexes: &mut Vec<usize>,
+        summary: &mut Summary,
+        issues: &mut Vec<Issue>,
+    ) {
+        let Some((segment, rest)) = segments.split_first() else {
+            return;
+        };
+
+        if rest.is_empty() {
+            self.apply_field(object, segment, indexes, summary, issues);
+            return;
+        }
+
+        match object.get_mut(&segment.key) {
+            Some(Value::Array(array)) if segment.array => {
+                for (i, value) in array.iter_mut().enumerate() {
+                    if let Value::Object(object) = value {
+                        indexes.push(i);
+                        self.walk(object, rest, indexes, summary, issues);
+                        indexes.pop();
+                    }
+                }
+            }
+            Some(Value::Object(object)) if !segment.array => self.walk(object, rest, indexes, summary, issues),
+            _ => {}
+        }
+    }
+
+    fn apply_field(
+        &self,
+        object: &mut Map<String, Value>,
+        segment: &Segment,
+        indexes: &mut Vec<usize>,
+        summary: &mut Summary,
+        issues: &mut Vec<Issue>,
+    ) {
+        let mut key = segment.key.as_str();
+
+        if self.delete {
+            if let Some(value) = object.shift_remove(key) {
+                self.check(summary);
+                issues.push(Issue::new(self.path, indexes.clone(), IssueCode::Deleted, value).fixed(Value::Null));
+            }
+            return;
+        }
+
+        if !object.contains_key(key) {
+            if let Some(default) = &self.default {
+                self.check(summary);
+                object.insert(key.into(), default.clone());
+                let issue = Issue::new(self.path, indexes.clone(), IssueCode::NotSet, Value::Null);
+                issues.push(issue.fixed(default.clone()));
+            }
+            return;
+        }
+
+        if let Some(rename) = &self.rename {
+            if object.contains_key(rename) {
+                warn!("Can't rename {}, because {rename} is occupied.", self.path);
+            } else if let Some(value) = object.shift_remove(key) {
+                let name = Value::String(key.into());
+                let issue = Issue::new(self.path, indexes.clone(), IssueCode::Renamed, name);
+                issues.push(issue.fixed(Value::String(rename.clone())));
+                object.insert(rename.clone(), value);
+                key = rename;
+            }
+        }
+
+        let Some(value) = object.get_mut(key) else {
+            return;
+        };
+
+        if segment.array {
+            if let Value::Array(array) = value {
+                for (i, value) in array.iter_mut().enumerate() {
+                    self.check(summary);
+                    indexes.push(i);
+                    self.modify(value, indexes, issues);
+                    indexes.pop();
+                }
+            }
+        } else {
+            self.check(summary);
+            self.modify(value, indexes, issues);
+        }
+    }
+
+    // Map, replace and cast a string value, and report the original value if it changed.
+    fn modify(&self, value: &mut Value, indexes: &[usize], issues: &mut Vec<Issue>) {
+        let Value::String(string) = value else {
+            return;
+        };
+        let original = string.clone();
+
+        if let Some(replacement) = self.map.get(string.as_str()) {
+            string.clone_from(replacement);
+        }
+        if let Some((regex, replacement)) = &self.pattern
+            && let Cow::Owned(replaced) = regex.replace_all(string, replacement.as_str())
+        {
+            *string = replaced;
+        }
+        if matches!(self.cast, Some(Cast::Number)) {
+            if let Ok(number) = string.trim().parse::<Number>() {
+                *value = Value::Number(number);
+            } else {
+                issues.push(Issue::new(
+                    self.path,
+                    indexes.to_vec(),
+                    IssueCode::Invalid,
+                    Value::String(original),
+                ));
+                return;
+            }
+        }
+
+        if value.as_str() != Some(original.as_str()) {
+            let issue = Issue::new(self.path, indexes.to_vec(), IssueCode::Invalid, Value::String(original));
+            // Like a normalized date, the fixed value depends on the original value, so it is summarized as normalized.
+            if self.pattern.is_some() || self.cast.is_some() {
+                issues.push(issue.normalized(value.clone()));
+            } else {
+                issues.push(issue.fixed(value.clone()));
+            }
+        }
+    }
+}
@@ -8,3 +8,45 @@ pub static BID_STATUS: LazyLock<HashSet<&str>> =
 // https://standard.open-contracting.org/latest/en/schema/codelists/#award-status
 pub static AWARD_STATUS: LazyLock<HashSet<&str>> =
     LazyLock::new(|| HashSet::from(["pending", "active", "cancelled", "unsuccessful"]));
+
+// https://standard.open-contracting.org/latest/en/schema/codelists/#method
+pub static PROCUREMENT_METHOD: LazyLock<HashSet<&str>> =
+    LazyLock::new(|| HashSet::from(["open", "selective", "limited", "direct"]));
+
+// https://standard.open-contracting.org/latest/en/schema/codelists/#tender-status
+pub static TENDER_STATUS: LazyLock<HashSet<&str>> = LazyLock::new(|| {
+    HashSet::from([
+        "planning",
+        "planned",
+        "active",
+        "cancelled",
+        "unsuccessful",
+        "complete",
+        "withdrawn",
+    ])
+});
+
+// https://standard.open-contracting.org/latest/en/schema/codelists/#contract-status
+pub static CONTRACT_STATUS: LazyLock<HashSet<&str>> =
+    LazyLock::new(|| HashSet::from(["pending", "active", "cancelled", "terminated"]));
+
+// https://standard.open-contracting.org/latest/en/schema/codelists/#party-role
+pub static PARTY_ROLE: LazyLock<HashSet<&str>> = LazyLock::new(|| {
+    HashSet::from([
+        "buyer",
+        "procuringEntity",
+        "supplier",
+        "tenderer",
+        "funder",
+        "enquirer",
+        "payer",
+        "payee",
+        "reviewBody",
+        "interestedParty",
+    ])
+});
+
+// Fields whose arrays are replaced, not merged, when compiling releases.
+// https://standard.open-contracting.org/latest/en/schema/merging/#whole-list-merge
+pub static WHOLE_LIST_MERGE: LazyLock<HashSet<&str>> =
+    LazyLock::new(|| HashSet::from(["additionalClassifications", "additionalIdentifiers", "changes"]));
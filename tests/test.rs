@@ -41,10 +41,257 @@ fn success_file() {
         .stdout(alt1.or(alt2));
 }
 
+#[rstest]
+#[case("gz")]
+#[case("bz2")]
+#[case("zst")]
+fn success_compressed_file(#[case] extension: &str) {
+    let alt1 = predicate::eq("{\"\": 1, \"[]\": 1}\n");
+    let alt2 = predicate::eq("{\"[]\": 1, \"\": 1}\n");
+    coverage(&[&format!("tests/fixtures/compressed/base_array.jsonl.{extension}")])
+        .success()
+        .stdout(alt1.or(alt2));
+}
+
+#[test]
+fn success_compressed_stdin() {
+    let alt1 = predicate::eq("{\"\": 1, \"[]\": 1}\n");
+    let alt2 = predicate::eq("{\"[]\": 1, \"\": 1}\n");
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["coverage", "-"])
+        .write_stdin(std::fs::read("tests/fixtures/compressed/base_array.jsonl.gz").unwrap())
+        .assert()
+        .success()
+        .stdout(alt1.or(alt2));
+}
+
+#[rstest]
+#[case("gz", "unexpected end of file")]
+#[case("bz2", "decompression not finished but EOF reached")]
+#[case("zst", "incomplete frame")]
+fn failure_compressed_truncated(#[case] extension: &str, #[case] message: &str) {
+    coverage(&[&format!("tests/fixtures/truncated/base_array.jsonl.{extension}")])
+        .failure()
+        .stderr(format!("Application error: {message}\n"));
+}
+
+#[rstest]
+#[case("array")]
+#[case("release-package")]
+#[case("record-package")]
+fn success_input_format(#[case] format: &str) {
+    let output = coverage(&["--input-format", format, &format!("tests/fixtures/input/{format}.json")])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let actual: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let expected = serde_json::json!({"": 2, "/": 2, "/ocid": 2, "/tag": 2, "/tag[]": 2});
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn error_input_format_record() {
+    let msg = " WARN  ocdscardinal::input > Record 3 has no compiledRelease, skipping.\n";
+    coverage(&[
+        "--input-format",
+        "record-package",
+        "tests/fixtures/input/record-package.json",
+    ])
+    .success()
+    .stderr(msg);
+}
+
+#[test]
+fn failure_input_format() {
+    let msg = "Application error: invalid type: map, expected a JSON array at line 1 column 1\n";
+    coverage(&["--input-format", "array", "tests/fixtures/input/release-package.json"])
+        .failure()
+        .stderr(msg);
+}
+
+#[test]
+fn failure_input_format_missing_key() {
+    let msg = "Application error: missing field `records` at line 11 column 1\n";
+    coverage(&[
+        "--input-format",
+        "record-package",
+        "tests/fixtures/input/release-package.json",
+    ])
+    .failure()
+    .stderr(msg);
+}
+
+#[test]
+fn failure_input_format_truncated() {
+    let msg = "Application error: EOF while parsing a string at line 4 column 6\n";
+    coverage(&["--input-format", "array", "tests/fixtures/truncated/array.json"])
+        .failure()
+        .stderr(msg);
+}
+
+#[test]
+fn failure_multiple_files_input_format() {
+    let msg = "Application error: tests/fixtures/input/release-package.json: invalid type: map, expected a JSON array at line 1 column 1\n";
+    coverage(&[
+        "--input-format",
+        "array",
+        "tests/fixtures/input/array.json",
+        "tests/fixtures/input/release-package.json",
+    ])
+    .failure()
+    .stderr(msg);
+}
+
+#[test]
+fn success_multiple_files() {
+    let alt1 = predicate::eq("{\"\": 2, \"[]\": 2}\n");
+    let alt2 = predicate::eq("{\"[]\": 2, \"\": 2}\n");
+    coverage(&[
+        "tests/fixtures/coverage/base_array.jsonl",
+        "tests/fixtures/compressed/base_array.jsonl.gz",
+    ])
+    .success()
+    .stdout(alt1.or(alt2));
+}
+
+#[test]
+fn success_directory() {
+    let alt1 = predicate::eq("{\"\": 3, \"[]\": 3}\n");
+    let alt2 = predicate::eq("{\"[]\": 3, \"\": 3}\n");
+    coverage(&["tests/fixtures/compressed"]).success().stdout(alt1.or(alt2));
+}
+
+#[test]
+fn error_multiple_files() {
+    let msg = " WARN  ocdscardinal > Line tests/fixtures/coverage/invalid_utf8.jsonl:1 caused an I/O error, skipping. [stream did not contain valid UTF-8]\n";
+    coverage(&[
+        "tests/fixtures/coverage/base_array.jsonl",
+        "tests/fixtures/coverage/invalid_utf8.jsonl",
+    ])
+    .success()
+    .stderr(msg);
+}
+
+#[test]
+fn success_multiple_files_prepare() {
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["prepare", "--output", "-", "--errors", "-"])
+        .args(&[
+            "tests/fixtures/coverage/base_array.jsonl",
+            "docs/examples/prepare.jsonl",
+        ])
+        .env("RAYON_NUM_THREADS", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "docs/examples/prepare.jsonl:1,ocds-213czf-1,/bids/details[]/status,0,,not set\n",
+        ))
+        .stderr(
+            " WARN  ocdscardinal > Line tests/fixtures/coverage/base_array.jsonl:1 is not a JSON object, skipping.\n",
+        );
+}
+
+#[test]
+fn failure_rules() {
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["prepare", "--output", "-", "--errors", "-"])
+        .args(&["--settings", "tests/fixtures/prepare/rules_invalid.ini"])
+        .arg("docs/examples/prepare.jsonl")
+        .assert()
+        .failure()
+        .stderr("Application error: rules.title: path must start with a slash, like /tender/title\n");
+}
+
+#[test]
+fn failure_compile_record_package() {
+    let message = "error: invalid value 'record-package' for '--input-format <INPUT_FORMAT>'\n";
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["compile", "--output", "-", "--input-format", "record-package"])
+        .arg("tests/fixtures/input/record-package.json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(message));
+}
+
+#[test]
+fn failure_results_to_csv_maps() {
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["results-to-csv", "--output", "-", "-"])
+        .write_stdin(r#"{"Buyer": {"B": {"R038": 0.5}}}"#)
+        .assert()
+        .failure()
+        .stderr("Application error: Maps: key must be set, with the --map option of the indicators command\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn success_results_to_csv_append() {
+    let mut tempfile = NamedTempFile::new().unwrap();
+    // An indicator code and a result that this version doesn't read.
+    let existing = "ocid,subject,code,result,buyer_id,procuring_entity_id,tenderer_id,created_at\n\
+                    F,OCID,R999,n/a,,,,2000-01-01T00:00:00Z\n\
+                    F,OCID,R024,0.01,,,,2000-01-01T00:00:00Z\n";
+    std::io::Write::write_all(&mut tempfile, existing.as_bytes()).unwrap();
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&[
+            "results-to-csv",
+            "--quiet",
+            "--output",
+            tempfile.path().to_str().unwrap(),
+        ])
+        .arg("docs/examples/results-to-csv.json")
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(tempfile.path()).unwrap();
+    assert!(content.starts_with(existing));
+    assert_eq!(content.lines().count(), 6);
+}
+
+#[test]
+fn error_r006_threshold() {
+    let msg = " WARN  ocdscardinal::indicators::r006 > R006 has no effect if neither threshold nor procurement_method_details is set.\n";
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["indicators", "--settings", "tests/fixtures/settings/R006.ini"])
+        .arg("docs/examples/R/006.jsonl")
+        .assert()
+        .success()
+        .stderr(msg);
+}
+
+#[test]
+fn error_r011_procurement_threshold() {
+    let msg = " WARN  ocdscardinal::indicators::r011 > R011 has no effect if procurement_threshold isn't set.\n";
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .args(&["indicators", "--settings", "tests/fixtures/settings/R011.ini"])
+        .arg("docs/examples/R/011.jsonl")
+        .assert()
+        .success()
+        .stderr(msg);
+}
+
 #[test]
 fn failure_directory() {
-    let message = "error: tests: Is a directory, not a file\n";
-    coverage(&["tests"])
+    let message = "error: tests/fixtures/input: Contains no *.jsonl* files\n";
+    coverage(&["tests/fixtures/input"])
+        .failure()
+        .stderr(predicate::str::starts_with(message));
+}
+
+#[test]
+fn failure_stdin_and_file() {
+    let message = "error: -: Standard input can't be combined with files\n";
+    coverage(&["-", "tests/fixtures/coverage/base_array.jsonl"])
         .failure()
         .stderr(predicate::str::starts_with(message));
 }